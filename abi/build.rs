@@ -26,16 +26,38 @@ fn main() {
                 "user_id",
                 "status",
                 "page_size",
-                "page",
+                "page_token",
                 "desc",
+                "include_cancelled",
+                "sort_by",
+                "statuses",
+                "match_mode",
             ],
         )
         .with_derive_builder_into(
             "reservation.ReservationFilter",
-            &["resource_id", "user_id", "status", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "desc",
+                "include_cancelled",
+                "sort_by",
+                "statuses",
+                "total_mode",
+                "page_cursor",
+                "match_mode",
+                "note",
+            ],
+        )
+        .with_derive_builder_option(
+            "reservation.ReservationFilter",
+            &["cursor", "created", "updated", "start", "end"],
+        )
+        .with_derive_builder_option(
+            "reservation.ReservationQuery",
+            &["start", "end", "created", "updated"],
         )
-        .with_derive_builder_option("reservation.ReservationFilter", &["cursor"])
-        .with_derive_builder_option("reservation.ReservationQuery", &["start", "end"])
         .with_type_attributes(
             &["reservation.ReservationFilter"],
             &[r#"#[builder(build_fn(name = "private_build"))]"#],
@@ -44,11 +66,19 @@ fn main() {
             &["reservation.ReservationFilter.page_size"],
             &["#[builder(setter(into), default = \"10\")]"],
         )
-        .compile(&["protos/reservation.proto"], &["protos"])
+        .compile(
+            &[
+                "protos/reservation.proto",
+                "protos/google/rpc/status.proto",
+                "protos/google/rpc/error_details.proto",
+            ],
+            &["protos"],
+        )
         .unwrap();
     //fs::remove_file("src/pb/google.protobuf.rs").unwrap();
     Command::new("cargo").args(["fmt"]).output().unwrap();
     println!("cargo:return-if-changed=protos/reservation.proto");
+    println!("cargo:return-if-changed=protos/google/rpc");
 }
 
 // trait BuilderExt {
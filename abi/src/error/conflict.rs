@@ -1,8 +1,11 @@
 use regex::Regex;
-use std::{collections::HashMap, convert::Infallible, str::FromStr};
+use sqlx::postgres::types::PgRange;
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr};
 
 use chrono::{DateTime, Utc};
 
+use crate::{convert_to_timestamp, types::NaiveRange, ConflictInfo, Reservation, TimeSlot};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReservationConflictInfo {
     Parsed(ReservationConflict),
@@ -20,6 +23,87 @@ pub struct ReservationWindow {
     pub end: DateTime<Utc>,
 }
 
+/// a reservation of a batch conflicting with existing reservations, or with another one of the
+/// same batch, which has no id
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchConflict {
+    pub index: usize,
+    pub new: ReservationWindow,
+    pub existing: Vec<Reservation>,
+}
+
+impl BatchConflict {
+    /// one ConflictInfo per existing reservation, or a bare one if they are not known
+    pub fn to_conflict_infos(&self) -> Vec<ConflictInfo> {
+        let info = ConflictInfo {
+            new: Some(self.new.clone().into()),
+            index: self.index as u32,
+            ..Default::default()
+        };
+        if self.existing.is_empty() {
+            return vec![info];
+        }
+        self.existing
+            .iter()
+            .map(|r| ConflictInfo {
+                old: Some(r.get_window().into()),
+                existing: Some(r.clone()),
+                ..info.clone()
+            })
+            .collect()
+    }
+}
+
+impl ReservationWindow {
+    pub fn new(rid: impl Into<String>, timespan: PgRange<DateTime<Utc>>) -> Self {
+        let range: NaiveRange<DateTime<Utc>> = timespan.into();
+        // in real world, reservation always have a bound
+        Self {
+            rid: rid.into(),
+            start: range.start.unwrap(),
+            end: range.end.unwrap(),
+        }
+    }
+}
+
+impl From<ReservationWindow> for TimeSlot {
+    fn from(w: ReservationWindow) -> Self {
+        Self {
+            resource_id: w.rid,
+            start: Some(convert_to_timestamp(w.start)),
+            end: Some(convert_to_timestamp(w.end)),
+        }
+    }
+}
+
+impl From<ReservationConflict> for ConflictInfo {
+    fn from(c: ReservationConflict) -> Self {
+        Self {
+            new: Some(c.new.into()),
+            old: Some(c.old.into()),
+            existing: None,
+            index: 0,
+        }
+    }
+}
+
+impl fmt::Display for ReservationConflictInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationConflictInfo::Parsed(c) => write!(
+                f,
+                "{} from {} to {} overlaps with existing reservation from {} to {}",
+                c.new.rid,
+                c.new.start.to_rfc3339(),
+                c.new.end.to_rfc3339(),
+                c.old.start.to_rfc3339(),
+                c.old.end.to_rfc3339()
+            ),
+            ReservationConflictInfo::Unparsed(s) => write!(f, "{}", s),
+        }
+    }
+}
+
 impl FromStr for ReservationConflictInfo {
     type Err = Infallible;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -1,8 +1,14 @@
 mod conflict;
+mod status;
 
 use sqlx::postgres::PgDatabaseError;
 
-pub use conflict::{ReservationConflict, ReservationConflictInfo, ReservationWindow};
+use crate::{Reservation, ReservationStatus, TimeSlot};
+
+pub use conflict::{
+    BatchConflict, ReservationConflict, ReservationConflictInfo, ReservationWindow,
+};
+pub use status::{ErrorDetails, ERROR_DOMAIN};
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     // #[error("data store disconnected")]
@@ -30,12 +36,24 @@ pub enum Error {
 
     // #[error("Invalid timespan for the reservation")]
     // InvalidReservationTimespan,
+    /// the conflict parsed from the database error, the existing reservations blocking the new
+    /// one and the free windows suggested instead. the last two are filled by the manager
     #[error("Conflict reservation")]
-    ConflictReservation(ReservationConflictInfo),
+    ConflictReservation {
+        info: Box<ReservationConflictInfo>,
+        existing: Vec<Reservation>,
+        suggestions: Vec<TimeSlot>,
+    },
+
+    #[error("Conflict reservations in the batch")]
+    ConflictReservations(Vec<BatchConflict>),
 
     #[error("No reservation found by the given condition")]
     NotFound,
 
+    #[error("No available slot found by the given condition")]
+    NoAvailableSlot,
+
     #[error("Invalid reservation id: {0}")]
     InvalidReservationId(i64),
 
@@ -49,8 +67,32 @@ pub enum Error {
     InvalidPageSize(i64),
     #[error("Invalid cursor: {0}")]
     InvalidCursor(i64),
+    #[error("Invalid page token: {0}")]
+    InvalidPageToken(String),
+    #[error("Invalid sort by: {0}")]
+    InvalidSortBy(i32),
     #[error("Invalid status: {0}")]
     InvalidStatus(i32),
+    #[error("Invalid update type: {0}")]
+    InvalidUpdateType(i32),
+    #[error("Invalid duration")]
+    InvalidDuration,
+    #[error("Invalid update mask: {0}")]
+    InvalidUpdateMask(String),
+    #[error("Invalid status transition: reservation is {0}, cannot change to {1}")]
+    InvalidTransition(ReservationStatus, ReservationStatus),
+
+    #[error("Reservation has been changed: expected version {0}, current version {1}")]
+    VersionMismatch(i64, i64),
+
+    #[error("Invalid suggestion count: {0}")]
+    InvalidSuggestionCount(u32),
+
+    #[error("Invalid bulk action: {0}")]
+    InvalidBulkAction(i32),
+
+    #[error("Missing selector of the reservations to change")]
+    InvalidSelector,
 
     #[error("Unknown error")]
     Unknown,
@@ -62,11 +104,38 @@ impl PartialEq for Error {
             // TDDO: this is not a good way to compare DB errors, but we dont do that in the code.
             (Self::DbError(_), Self::DbError(_)) => true,
             (Self::InvalidTime, Self::InvalidTime) => true,
-            (Self::ConflictReservation(v1), Self::ConflictReservation(v2)) => v1 == v2,
+            (
+                Self::ConflictReservation {
+                    info: v1,
+                    existing: r1,
+                    suggestions: s1,
+                },
+                Self::ConflictReservation {
+                    info: v2,
+                    existing: r2,
+                    suggestions: s2,
+                },
+            ) => v1 == v2 && r1 == r2 && s1 == s2,
+            (Self::ConflictReservations(v1), Self::ConflictReservations(v2)) => v1 == v2,
             (Self::NotFound, Self::NotFound) => true,
+            (Self::NoAvailableSlot, Self::NoAvailableSlot) => true,
             (Self::InvalidReservationId(v1), Self::InvalidReservationId(v2)) => v1 == v2,
             (Self::InvalidUserId(v1), Self::InvalidUserId(v2)) => v1 == v2,
             (Self::InvalidResourceId(v1), Self::InvalidResourceId(v2)) => v1 == v2,
+            (Self::InvalidPageSize(v1), Self::InvalidPageSize(v2)) => v1 == v2,
+            (Self::InvalidPageToken(v1), Self::InvalidPageToken(v2)) => v1 == v2,
+            (Self::InvalidSortBy(v1), Self::InvalidSortBy(v2)) => v1 == v2,
+            (Self::InvalidStatus(v1), Self::InvalidStatus(v2)) => v1 == v2,
+            (Self::InvalidUpdateType(v1), Self::InvalidUpdateType(v2)) => v1 == v2,
+            (Self::InvalidDuration, Self::InvalidDuration) => true,
+            (Self::InvalidUpdateMask(v1), Self::InvalidUpdateMask(v2)) => v1 == v2,
+            (Self::InvalidTransition(f1, t1), Self::InvalidTransition(f2, t2)) => {
+                f1 == f2 && t1 == t2
+            }
+            (Self::VersionMismatch(e1, a1), Self::VersionMismatch(e2, a2)) => e1 == e2 && a1 == a2,
+            (Self::InvalidSuggestionCount(v1), Self::InvalidSuggestionCount(v2)) => v1 == v2,
+            (Self::InvalidBulkAction(v1), Self::InvalidBulkAction(v2)) => v1 == v2,
+            (Self::InvalidSelector, Self::InvalidSelector) => true,
             (Self::Unknown, Self::Unknown) => true,
             _ => false,
         }
@@ -79,9 +148,11 @@ impl From<sqlx::Error> for Error {
             sqlx::Error::Database(e) => {
                 let err: &PgDatabaseError = e.downcast_ref();
                 match (err.code(), err.schema(), err.table()) {
-                    ("23P01", Some(_rsvp), Some(_reservations)) => {
-                        Error::ConflictReservation(err.detail().unwrap().parse().unwrap())
-                    }
+                    ("23P01", Some(_rsvp), Some(_reservations)) => Error::ConflictReservation {
+                        info: Box::new(err.detail().unwrap().parse().unwrap()),
+                        existing: vec![],
+                        suggestions: vec![],
+                    },
                     _ => Error::DbError(sqlx::Error::Database(e)),
                 }
             }
@@ -93,9 +164,9 @@ impl From<sqlx::Error> for Error {
 
 impl From<Error> for tonic::Status {
     fn from(e: Error) -> Self {
-        match e {
+        let code = match e {
             Error::ConfigReadError | Error::ConfigParseError | Error::DbError(_) => {
-                tonic::Status::internal(e.to_string())
+                tonic::Code::Internal
             }
 
             Error::InvalidTime
@@ -104,16 +175,53 @@ impl From<Error> for tonic::Status {
             | Error::InvalidResourceId(_)
             | Error::InvalidPageSize(_)
             | Error::InvalidCursor(_)
-            | Error::InvalidStatus(_) => tonic::Status::invalid_argument(e.to_string()),
-            Error::ConflictReservation(info) => {
-                tonic::Status::failed_precondition(format!("Conflict reservation: {:?}", info))
+            | Error::InvalidPageToken(_)
+            | Error::InvalidSortBy(_)
+            | Error::InvalidStatus(_)
+            | Error::InvalidUpdateType(_)
+            | Error::InvalidDuration
+            | Error::InvalidUpdateMask(_)
+            | Error::InvalidSuggestionCount(_)
+            | Error::InvalidBulkAction(_)
+            | Error::InvalidSelector => tonic::Code::InvalidArgument,
+            Error::InvalidTransition(..)
+            | Error::ConflictReservation { .. }
+            | Error::ConflictReservations(_) => tonic::Code::FailedPrecondition,
+            Error::VersionMismatch(..) => tonic::Code::Aborted,
+            Error::NotFound | Error::NoAvailableSlot => tonic::Code::NotFound,
+
+            Error::Unknown => tonic::Code::Unknown,
+        };
+        let message = match &e {
+            Error::ConflictReservation { info, existing, .. } => conflict_message(info, existing),
+            Error::ConflictReservations(conflicts) => {
+                let indexes: Vec<_> = conflicts.iter().map(|c| c.index.to_string()).collect();
+                format!(
+                    "Conflict reservations: reservations at {} of the batch are taken",
+                    indexes.join(", ")
+                )
             }
-            Error::NotFound => tonic::Status::not_found(
-                "No reservation found by the given condition
-                ",
-            ),
+            _ => e.to_string(),
+        };
+        let details = e.status_details(code, &message);
+        tonic::Status::with_details(code, message, details)
+    }
+}
 
-            Error::Unknown => tonic::Status::unknown("unknown error"),
-        }
+fn conflict_message(info: &ReservationConflictInfo, existing: &[Reservation]) -> String {
+    if existing.is_empty() {
+        return format!("Conflict reservation: {}", info);
     }
+    let blocking: Vec<_> = existing
+        .iter()
+        .map(|r| {
+            let status = ReservationStatus::from_i32(r.status).unwrap_or_default();
+            format!("{}'s reservation #{} ({})", r.user_id, r.id, status)
+        })
+        .collect();
+    format!(
+        "Conflict reservation: {}, conflicts with {}",
+        info,
+        blocking.join(", ")
+    )
 }
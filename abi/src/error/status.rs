@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_types::Any;
+use tonic::{codegen::Bytes, Code};
+
+use crate::{
+    pb::google::rpc::{bad_request::FieldViolation, BadRequest, ErrorInfo, Status},
+    ConflictInfo, ErrorReason, Reservation, SlotSuggestions, TimeSlot,
+};
+
+use super::{Error, ReservationConflictInfo};
+
+/// domain of the reasons in the ErrorInfo of the status details
+pub const ERROR_DOMAIN: &str = "reservation";
+
+const REASON_PREFIX: &str = "ERROR_REASON_";
+const ERROR_INFO_TYPE: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+const BAD_REQUEST_TYPE: &str = "type.googleapis.com/google.rpc.BadRequest";
+const CONFLICT_INFO_TYPE: &str = "type.googleapis.com/reservation.ConflictInfo";
+const SLOT_SUGGESTIONS_TYPE: &str = "type.googleapis.com/reservation.SlotSuggestions";
+
+/// machine readable details of an error, decoded from the grpc status details
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ErrorDetails {
+    pub reason: ErrorReason,
+    pub metadata: HashMap<String, String>,
+    pub field_violations: Vec<FieldViolation>,
+    pub conflicts: Vec<ConflictInfo>,
+    pub suggestions: Vec<TimeSlot>,
+}
+
+impl Error {
+    /// stable reason of the error, clients could switch on it
+    pub fn reason(&self) -> ErrorReason {
+        match self {
+            Error::ConfigReadError => ErrorReason::ConfigReadError,
+            Error::ConfigParseError => ErrorReason::ConfigParseError,
+            Error::DbError(_) => ErrorReason::DbError,
+            Error::InvalidTime => ErrorReason::InvalidTime,
+            Error::ConflictReservation { .. } | Error::ConflictReservations(_) => {
+                ErrorReason::ConflictReservation
+            }
+            Error::NotFound => ErrorReason::NotFound,
+            Error::NoAvailableSlot => ErrorReason::NoAvailableSlot,
+            Error::InvalidReservationId(_) => ErrorReason::InvalidReservationId,
+            Error::InvalidUserId(_) => ErrorReason::InvalidUserId,
+            Error::InvalidResourceId(_) => ErrorReason::InvalidResourceId,
+            Error::InvalidPageSize(_) => ErrorReason::InvalidPageSize,
+            Error::InvalidCursor(_) => ErrorReason::InvalidCursor,
+            Error::InvalidPageToken(_) => ErrorReason::InvalidPageToken,
+            Error::InvalidSortBy(_) => ErrorReason::InvalidSortBy,
+            Error::InvalidStatus(_) => ErrorReason::InvalidStatus,
+            Error::InvalidUpdateType(_) => ErrorReason::InvalidUpdateType,
+            Error::InvalidDuration => ErrorReason::InvalidDuration,
+            Error::InvalidUpdateMask(_) => ErrorReason::InvalidUpdateMask,
+            Error::InvalidTransition(..) => ErrorReason::InvalidTransition,
+            Error::VersionMismatch(..) => ErrorReason::VersionMismatch,
+            Error::InvalidSuggestionCount(_) => ErrorReason::InvalidSuggestionCount,
+            Error::InvalidBulkAction(_) => ErrorReason::InvalidBulkAction,
+            Error::InvalidSelector => ErrorReason::InvalidSelector,
+            Error::Unknown => ErrorReason::Unknown,
+        }
+    }
+
+    /// request field at fault, for validation errors
+    fn field(&self) -> Option<&'static str> {
+        match self {
+            Error::InvalidTime => Some("start"),
+            Error::InvalidReservationId(_) => Some("id"),
+            Error::InvalidUserId(_) => Some("user_id"),
+            Error::InvalidResourceId(_) => Some("resource_id"),
+            Error::InvalidPageSize(_) => Some("page_size"),
+            Error::InvalidCursor(_) => Some("cursor"),
+            Error::InvalidPageToken(_) => Some("page_token"),
+            Error::InvalidSortBy(_) => Some("sort_by"),
+            Error::InvalidStatus(_) => Some("status"),
+            Error::InvalidUpdateType(_) => Some("op"),
+            Error::InvalidDuration => Some("min_duration"),
+            Error::InvalidUpdateMask(_) => Some("update_mask"),
+            Error::InvalidSuggestionCount(_) => Some("suggestions"),
+            Error::InvalidBulkAction(_) => Some("action"),
+            Error::InvalidSelector => Some("selector"),
+            _ => None,
+        }
+    }
+
+    fn metadata(&self) -> HashMap<String, String> {
+        let pairs = match self {
+            Error::InvalidReservationId(id) | Error::InvalidCursor(id) => {
+                vec![("value", id.to_string())]
+            }
+            Error::InvalidPageSize(size) => vec![("value", size.to_string())],
+            Error::InvalidSuggestionCount(n) => vec![("value", n.to_string())],
+            Error::InvalidStatus(v)
+            | Error::InvalidUpdateType(v)
+            | Error::InvalidSortBy(v)
+            | Error::InvalidBulkAction(v) => {
+                vec![("value", v.to_string())]
+            }
+            Error::InvalidUserId(v)
+            | Error::InvalidResourceId(v)
+            | Error::InvalidPageToken(v)
+            | Error::InvalidUpdateMask(v) => vec![("value", v.clone())],
+            Error::ConflictReservations(conflicts) => {
+                let indexes: Vec<_> = conflicts.iter().map(|c| c.index.to_string()).collect();
+                vec![("indexes", indexes.join(","))]
+            }
+            Error::InvalidTransition(from, to) => {
+                vec![("from", from.to_string()), ("to", to.to_string())]
+            }
+            Error::VersionMismatch(expected, current) => vec![
+                ("expected_version", expected.to_string()),
+                ("current_version", current.to_string()),
+            ],
+            Error::ConflictReservation { info, existing, .. } => {
+                let mut pairs = vec![];
+                if let ReservationConflictInfo::Parsed(c) = info.as_ref() {
+                    pairs.push(("resource_id", c.new.rid.clone()));
+                }
+                if !existing.is_empty() {
+                    let ids: Vec<_> = existing.iter().map(|r| r.id.to_string()).collect();
+                    pairs.push(("reservation_ids", ids.join(",")));
+                }
+                pairs
+            }
+            _ => vec![],
+        };
+        pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+
+    /// encoded google.rpc.Status with the details of the error
+    pub(crate) fn status_details(&self, code: Code, message: &str) -> Bytes {
+        let reason = self.reason().as_str_name();
+        let mut details = vec![pack(
+            ERROR_INFO_TYPE,
+            &ErrorInfo {
+                reason: reason.trim_start_matches(REASON_PREFIX).to_string(),
+                domain: ERROR_DOMAIN.to_string(),
+                metadata: self.metadata(),
+            },
+        )];
+        if let Some(field) = self.field() {
+            details.push(pack(
+                BAD_REQUEST_TYPE,
+                &BadRequest {
+                    field_violations: vec![FieldViolation {
+                        field: field.to_string(),
+                        description: self.to_string(),
+                    }],
+                },
+            ));
+        }
+        if let Error::ConflictReservation {
+            info,
+            existing,
+            suggestions,
+        } = self
+        {
+            details.extend(
+                conflict_infos(info, existing)
+                    .iter()
+                    .map(|c| pack(CONFLICT_INFO_TYPE, c)),
+            );
+            if !suggestions.is_empty() {
+                let slots = SlotSuggestions {
+                    slots: suggestions.clone(),
+                };
+                details.push(pack(SLOT_SUGGESTIONS_TYPE, &slots));
+            }
+        }
+        if let Error::ConflictReservations(conflicts) = self {
+            details.extend(
+                conflicts
+                    .iter()
+                    .flat_map(|c| c.to_conflict_infos())
+                    .map(|c| pack(CONFLICT_INFO_TYPE, &c)),
+            );
+        }
+        Status {
+            code: code as i32,
+            message: message.to_string(),
+            details,
+        }
+        .encode_to_vec()
+        .into()
+    }
+}
+
+impl ErrorDetails {
+    /// decode the details of the status, None if it has no ErrorInfo of the reservation domain
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        let status = Status::decode(status.details()).ok()?;
+        let mut ret: Option<ErrorDetails> = None;
+        let mut violations = vec![];
+        let mut conflicts = vec![];
+        let mut suggestions = vec![];
+        for any in status.details {
+            match any.type_url.as_str() {
+                ERROR_INFO_TYPE => {
+                    let info = ErrorInfo::decode(any.value.as_slice()).ok()?;
+                    if info.domain != ERROR_DOMAIN {
+                        continue;
+                    }
+                    let name = format!("{}{}", REASON_PREFIX, info.reason);
+                    ret = Some(ErrorDetails {
+                        reason: ErrorReason::from_str_name(&name).unwrap_or_default(),
+                        metadata: info.metadata,
+                        ..Default::default()
+                    });
+                }
+                BAD_REQUEST_TYPE => {
+                    let req = BadRequest::decode(any.value.as_slice()).ok()?;
+                    violations.extend(req.field_violations);
+                }
+                CONFLICT_INFO_TYPE => {
+                    conflicts.push(ConflictInfo::decode(any.value.as_slice()).ok()?);
+                }
+                SLOT_SUGGESTIONS_TYPE => {
+                    let slots = SlotSuggestions::decode(any.value.as_slice()).ok()?;
+                    suggestions.extend(slots.slots);
+                }
+                _ => {}
+            }
+        }
+        ret.map(|details| ErrorDetails {
+            field_violations: violations,
+            conflicts,
+            suggestions,
+            ..details
+        })
+    }
+}
+
+/// one ConflictInfo per existing reservation if they are known, otherwise the parsed one
+fn conflict_infos(info: &ReservationConflictInfo, existing: &[Reservation]) -> Vec<ConflictInfo> {
+    let parsed = match info {
+        ReservationConflictInfo::Parsed(c) => Some(c),
+        ReservationConflictInfo::Unparsed(_) => None,
+    };
+    if existing.is_empty() {
+        return parsed.map(|c| c.clone().into()).into_iter().collect();
+    }
+    let new: Option<TimeSlot> = parsed.map(|c| c.new.clone().into());
+    existing
+        .iter()
+        .map(|r| ConflictInfo {
+            new: new.clone(),
+            old: Some(r.get_window().into()),
+            existing: Some(r.clone()),
+            index: 0,
+        })
+        .collect()
+}
+
+fn pack(type_url: &str, msg: &impl Message) -> Any {
+    Any {
+        type_url: type_url.to_string(),
+        value: msg.encode_to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BatchConflict;
+
+    const ERR_MSG: &str = "Key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\")) conflicts with existing key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-25 22:00:00+00\",\"2022-12-28 19:00:00+00\")).";
+
+    #[test]
+    fn conflict_status_should_carry_conflict_info() {
+        let status: tonic::Status = Error::ConflictReservation {
+            info: Box::new(ERR_MSG.parse().unwrap()),
+            existing: vec![],
+            suggestions: vec![],
+        }
+        .into();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert!(status.message().contains("ocean-view-room-713"));
+        let details = ErrorDetails::from_status(&status).unwrap();
+        assert_eq!(details.reason, ErrorReason::ConflictReservation);
+        assert_eq!(details.metadata["resource_id"], "ocean-view-room-713");
+        assert!(details.field_violations.is_empty());
+        assert_eq!(details.conflicts.len(), 1);
+        let conflict = details.conflicts[0].clone();
+        assert_eq!(conflict.existing, None);
+        assert_eq!(conflict.new.unwrap().resource_id, "ocean-view-room-713");
+        assert_eq!(
+            conflict.old.unwrap().end.unwrap().seconds,
+            "2022-12-28T19:00:00Z"
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+                .timestamp()
+        );
+    }
+
+    #[test]
+    fn conflict_status_should_carry_existing_reservations() {
+        let mut existing = Reservation::new_pending(
+            "alice",
+            "ocean-view-room-713",
+            "2022-12-25T22:00:00Z".parse().unwrap(),
+            "2022-12-28T19:00:00Z".parse().unwrap(),
+            "",
+        );
+        existing.id = 812;
+        existing.status = crate::ReservationStatus::Confirmed as i32;
+        let suggested = TimeSlot {
+            resource_id: "ocean-view-room-713".into(),
+            start: existing.end.clone(),
+            end: "2023-01-01T19:00:00Z".parse().ok(),
+        };
+        let status: tonic::Status = Error::ConflictReservation {
+            info: Box::new(ERR_MSG.parse().unwrap()),
+            existing: vec![existing.clone()],
+            suggestions: vec![suggested.clone()],
+        }
+        .into();
+        assert!(status
+            .message()
+            .ends_with("conflicts with alice's reservation #812 (confirmed)"));
+        let details = ErrorDetails::from_status(&status).unwrap();
+        assert_eq!(details.metadata["reservation_ids"], "812");
+        assert_eq!(details.conflicts.len(), 1);
+        let conflict = details.conflicts[0].clone();
+        assert_eq!(conflict.new.unwrap().resource_id, "ocean-view-room-713");
+        assert_eq!(conflict.old.unwrap().start, existing.start);
+        assert_eq!(conflict.existing, Some(existing));
+        assert_eq!(details.suggestions, vec![suggested]);
+    }
+
+    #[test]
+    fn batch_conflict_status_should_carry_every_conflict() {
+        let new = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let room = new("room-1", "2022-12-26T15:00:00Z", "2022-12-26T17:00:00Z");
+        let mut existing = new(
+            "projector-1",
+            "2022-12-26T14:00:00Z",
+            "2022-12-26T16:00:00Z",
+        );
+        existing.id = 42;
+        let conflicts = vec![
+            BatchConflict {
+                index: 1,
+                new: new(
+                    "projector-1",
+                    "2022-12-26T15:00:00Z",
+                    "2022-12-26T17:00:00Z",
+                )
+                .get_window(),
+                existing: vec![existing.clone()],
+            },
+            BatchConflict {
+                index: 2,
+                new: new("room-1", "2022-12-26T16:00:00Z", "2022-12-26T18:00:00Z").get_window(),
+                existing: vec![room.clone()],
+            },
+        ];
+        let status: tonic::Status = Error::ConflictReservations(conflicts).into();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        let details = ErrorDetails::from_status(&status).unwrap();
+        assert_eq!(details.reason, ErrorReason::ConflictReservation);
+        assert_eq!(details.metadata["indexes"], "1,2");
+        let indexes: Vec<_> = details.conflicts.iter().map(|c| c.index).collect();
+        assert_eq!(indexes, vec![1, 2]);
+        assert_eq!(details.conflicts[0].existing, Some(existing));
+        assert_eq!(details.conflicts[1].existing, Some(room));
+    }
+
+    #[test]
+    fn invalid_argument_status_should_carry_field_violation() {
+        let status: tonic::Status = Error::InvalidPageSize(1000).into();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let details = ErrorDetails::from_status(&status).unwrap();
+        assert_eq!(details.reason, ErrorReason::InvalidPageSize);
+        assert_eq!(details.metadata["value"], "1000");
+        assert_eq!(details.field_violations.len(), 1);
+        assert_eq!(details.field_violations[0].field, "page_size");
+        assert_eq!(
+            details.field_violations[0].description,
+            "Invalid page size: 1000"
+        );
+        assert!(details.conflicts.is_empty());
+        assert!(details.suggestions.is_empty());
+    }
+
+    #[test]
+    fn status_without_details_should_not_decode() {
+        let status = tonic::Status::internal("oops");
+        assert_eq!(ErrorDetails::from_status(&status), None);
+
+        let status: tonic::Status = Error::VersionMismatch(1, 2).into();
+        let details = ErrorDetails::from_status(&status).unwrap();
+        assert_eq!(details.reason, ErrorReason::VersionMismatch);
+        assert_eq!(details.metadata["expected_version"], "1");
+        assert_eq!(details.metadata["current_version"], "2");
+    }
+}
@@ -5,10 +5,15 @@ mod types;
 mod utils;
 
 pub use config::*;
-pub use error::{Error, ReservationConflict, ReservationConflictInfo, ReservationWindow};
+pub use error::{
+    BatchConflict, Error, ErrorDetails, ReservationConflict, ReservationConflictInfo,
+    ReservationWindow, ERROR_DOMAIN,
+};
 pub use pb::*;
 pub use utils::*;
 
+use sqlx::{Postgres, QueryBuilder};
+
 pub type ReservationId = i64;
 pub type UserId = String;
 pub type ResourceId = String;
@@ -30,8 +35,9 @@ pub trait Normalizer: Validator {
     fn do_normalize(&mut self);
 }
 
+/// build sql for the data structure, with the values bound as parameters
 pub trait ToSql {
-    fn to_sql(&self) -> Result<String, Error>;
+    fn to_sql(&self) -> Result<QueryBuilder<'static, Postgres>, Error>;
 }
 
 // database equivalent of the "reservation_status" enum
@@ -42,6 +48,17 @@ pub enum RsvpStatus {
     Pending,
     Confirmed,
     Blocked,
+    Cancelled,
+}
+
+// database equivalent of the "reservation_update_type" enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "reservation_update_type", rename_all = "lowercase")]
+pub enum RsvpUpdateType {
+    Unknown,
+    Create,
+    Update,
+    Delete,
 }
 
 impl Validator for ReservationId {
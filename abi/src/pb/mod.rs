@@ -1,3 +1,10 @@
 #[allow(clippy::all, non_camel_case_types)]
 mod reservation;
 pub use reservation::*;
+
+#[allow(clippy::all, non_camel_case_types)]
+pub mod google {
+    pub mod rpc {
+        include!("google.rpc.rs");
+    }
+}
@@ -1,5 +1,15 @@
+/// a time range, start is inclusive and end is exclusive. If a bound is not set, the range has no such bound
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeRange {
+    #[prost(message, optional, tag = "1")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "2")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
 /// core reservation object. Contains all the information for a reservation
-/// if ListenResponse op is DELETE, only id will be populated
+/// if ListenResponse op is DELETE, it is the reservation as it was before the delete
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Reservation {
     /// unique id for the reservation, if put into the ReservationRequest, id should be empty
@@ -23,71 +33,181 @@ pub struct Reservation {
     /// extra note
     #[prost(string, tag = "7")]
     pub note: ::prost::alloc::string::String,
+    /// when the reservation was cancelled, only set for cancelled reservations
+    #[prost(message, optional, tag = "8")]
+    pub cancelled_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// why the reservation was cancelled
+    #[prost(string, tag = "9")]
+    pub cancel_reason: ::prost::alloc::string::String,
+    /// when the reservation was made, maintained by the server
+    #[prost(message, optional, tag = "10")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// when the reservation was last changed, maintained by the server
+    #[prost(message, optional, tag = "11")]
+    pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// bumped by the server on every change, send it back as expected_version to avoid overwriting others' changes
+    #[prost(int64, tag = "12")]
+    pub version: i64,
 }
 /// to make a reservation, send a ReservationRequest with Reservation object (id should be empty)
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReserveRequest {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// number of free windows to suggest if the reservation conflicts, 0 means no suggestion.
+    /// The windows are sent in the grpc status details of the conflict error
+    #[prost(uint32, tag = "2")]
+    pub suggestions: u32,
 }
 /// Created a reservation will be returned in ReserveResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReserveResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
-/// To update a reservation, send an UpdateRequest. Only note is updateable
+/// to make several reservations at once, send a ReserveManyRequest. Either all of them are made
+/// or none is
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveManyRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// Created reservations will be returned in ReserveManyResponse, in the order of the request
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveManyResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To update a reservation, send an UpdateRequest. Only the fields listed in update_mask
+/// are updated, the updatable fields are: note, start, end, resource_id, user_id, status.
+/// A cancelled reservation could not be updated, and to cancel one, send a CancelRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
-    #[prost(string, tag = "2")]
-    pub note: ::prost::alloc::string::String,
+    /// new values of the masked fields, id of the reservation is ignored
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+    /// fields to update
+    #[prost(message, optional, tag = "3")]
+    pub update_mask: ::core::option::Option<::prost_types::FieldMask>,
+    /// if set, only update the reservation if its version still matches
+    #[prost(int64, optional, tag = "4")]
+    pub expected_version: ::core::option::Option<i64>,
 }
 /// Updated reservation will be returned in UpdateResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+/// To move a reservation to another time window or resource, send a RescheduleRequest.
+/// id and status of the reservation stay the same
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescheduleRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    /// new start time for the reservation
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// new end time for the reservation
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// new resource id for the reservation. If empty, keep the current resource
+    #[prost(string, tag = "4")]
+    pub resource_id: ::prost::alloc::string::String,
+}
+/// Rescheduled reservation will be returned in RescheduleResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescheduleResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
 /// To change a reservation from pending to confirmed, send a ConfirmRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
+    /// if set, only confirm the reservation if its version still matches
+    #[prost(int64, optional, tag = "2")]
+    pub expected_version: ::core::option::Option<i64>,
 }
 /// Confirmed reservation will be returned in ConfirmResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+/// To move a reservation to another status, send a TransitionRequest. Allowed transitions are:
+/// pending -> confirmed | blocked, confirmed -> blocked, blocked -> pending,
+/// pending | confirmed | blocked -> cancelled. A cancelled reservation could not be moved any more.
+/// Moving to the current status does nothing
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransitionRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(enumeration = "ReservationStatus", tag = "2")]
+    pub status: i32,
+}
+/// Reservation with the new status will be returned in TransitionResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransitionResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
 /// To cancel a reservation, send a CancelRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
+    /// why the reservation is cancelled
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+    /// if set, only cancel the reservation if its version still matches
+    #[prost(int64, optional, tag = "3")]
+    pub expected_version: ::core::option::Option<i64>,
 }
 /// Cancel a reservation will be returned in CancelResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
 /// To get a reservation, send a GetRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
+    /// if true, return the reservation even if it is cancelled
+    #[prost(bool, tag = "2")]
+    pub include_cancelled: bool,
 }
 /// Reservation will be returned in GetResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
 /// Query reservations with user id,resource id,start time,end time,and status
-#[derive(derive_builder::Builder, Clone, PartialEq, ::prost::Message)]
+#[derive(derive_builder::Builder)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationQuery {
     /// request id for the reservation query. If empty, query all resources
     #[prost(string, tag = "1")]
@@ -97,7 +217,7 @@ pub struct ReservationQuery {
     #[prost(string, tag = "2")]
     #[builder(setter(into), default)]
     pub user_id: ::prost::alloc::string::String,
-    /// use status to filter result. if UNKNOWN, return all reservations
+    /// use status to filter result. if UNKNOWN and statuses is empty, return reservations in any status
     #[prost(enumeration = "ReservationStatus", tag = "3")]
     #[builder(setter(into), default)]
     pub status: i32,
@@ -110,18 +230,47 @@ pub struct ReservationQuery {
     #[builder(setter(into, strip_option), default)]
     pub end: ::core::option::Option<::prost_types::Timestamp>,
     /// sort direction for the query
-    ///
-    /// // current pagey for the query
-    /// int32 page = 6;
-    /// //page size for the query
-    /// int64 page_size = 7;
-    /// //sort direction for the query
-    /// bool desc = 8;
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// if true, cancelled reservations are returned as well
+    #[prost(bool, tag = "7")]
+    #[builder(setter(into), default)]
+    pub include_cancelled: bool,
+    /// sort key for the query, by start time if not set
+    #[prost(enumeration = "ReservationSortBy", tag = "8")]
+    #[builder(setter(into), default)]
+    pub sort_by: i32,
+    /// only return reservations made in this time range
+    #[prost(message, optional, tag = "9")]
+    #[builder(setter(into, strip_option), default)]
+    pub created: ::core::option::Option<TimeRange>,
+    /// only return reservations last changed in this time range
+    #[prost(message, optional, tag = "10")]
+    #[builder(setter(into, strip_option), default)]
+    pub updated: ::core::option::Option<TimeRange>,
+    /// return reservations in any of these status, together with status if it is set.
+    /// cancelled reservations are returned only if CANCELLED is listed or include_cancelled is set
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "11")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// how reservations are matched against start and end, by containment if not set
+    #[prost(enumeration = "ReservationMatchMode", tag = "12")]
+    #[builder(setter(into), default)]
+    pub match_mode: i32,
+    /// max number of reservations to return, 0 means no limit. If set, results are ordered by
+    /// start time and id, so sort_by could only be START or unset, and the token of the next page
+    /// is returned in the x-next-page-token response metadata
+    #[prost(int64, tag = "13")]
+    #[builder(setter(into), default)]
+    pub page_size: i64,
+    /// continuation token from the previous page, empty for the first page
+    #[prost(string, tag = "14")]
+    #[builder(setter(into), default)]
+    pub page_token: ::prost::alloc::string::String,
 }
 /// To query a reservation, send a QueryRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryRequest {
     #[prost(message, optional, tag = "1")]
@@ -130,6 +279,7 @@ pub struct QueryRequest {
 /// To query reservations, order by reservation id
 #[derive(derive_builder::Builder)]
 #[builder(build_fn(name = "private_build"))]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationFilter {
     /// request id for the reservation query. If empty, query all resources
@@ -140,11 +290,11 @@ pub struct ReservationFilter {
     #[prost(string, tag = "2")]
     #[builder(setter(into), default)]
     pub user_id: ::prost::alloc::string::String,
-    /// use status to filter result. if UNKNOWN, return all reservations
+    /// use status to filter result. if UNKNOWN and statuses is empty, return reservations in any status
     #[prost(enumeration = "ReservationStatus", tag = "3")]
     #[builder(setter(into), default)]
     pub status: i32,
-    /// previous cursor
+    /// previous cursor, a reservation id from FilterPager prev or next
     #[prost(int64, optional, tag = "4")]
     #[builder(setter(into, strip_option), default)]
     pub cursor: ::core::option::Option<i64>,
@@ -156,23 +306,78 @@ pub struct ReservationFilter {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// if true, cancelled reservations are returned as well
+    #[prost(bool, tag = "7")]
+    #[builder(setter(into), default)]
+    pub include_cancelled: bool,
+    /// sort key for the filter, by reservation id if not set
+    #[prost(enumeration = "ReservationSortBy", tag = "8")]
+    #[builder(setter(into), default)]
+    pub sort_by: i32,
+    /// only return reservations made in this time range
+    #[prost(message, optional, tag = "9")]
+    #[builder(setter(into, strip_option), default)]
+    pub created: ::core::option::Option<TimeRange>,
+    /// only return reservations last changed in this time range
+    #[prost(message, optional, tag = "10")]
+    #[builder(setter(into, strip_option), default)]
+    pub updated: ::core::option::Option<TimeRange>,
+    /// return reservations in any of these status, together with status if it is set.
+    /// cancelled reservations are returned only if CANCELLED is listed or include_cancelled is set
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "11")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// if set, the total number of matched reservations is returned in the pager
+    #[prost(enumeration = "ReservationTotalMode", tag = "12")]
+    #[builder(setter(into), default)]
+    pub total_mode: i32,
+    /// opaque cursor from FilterPager prev_cursor or next_cursor, takes precedence over cursor.
+    /// It must be used with the same sort_by and desc it was returned for
+    #[prost(string, tag = "13")]
+    #[builder(setter(into), default)]
+    pub page_cursor: ::prost::alloc::string::String,
+    /// only return reservations in this time window, if a bound is not set, the window has no such bound
+    #[prost(message, optional, tag = "14")]
+    #[builder(setter(into, strip_option), default)]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "15")]
+    #[builder(setter(into, strip_option), default)]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// how reservations are matched against start and end, by containment if not set
+    #[prost(enumeration = "ReservationMatchMode", tag = "16")]
+    #[builder(setter(into), default)]
+    pub match_mode: i32,
+    /// only return reservations whose note contains this text, case insensitive
+    #[prost(string, tag = "17")]
+    #[builder(setter(into), default)]
+    pub note: ::prost::alloc::string::String,
 }
 /// To filter a reservation, send a QueryRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterRequest {
     #[prost(message, optional, tag = "1")]
     pub filter: ::core::option::Option<ReservationFilter>,
 }
 /// filter pager info
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterPager {
+    /// reservation id cursors of the previous and next page, only set if page_cursor is not used
     #[prost(int64, optional, tag = "1")]
     pub prev: ::core::option::Option<i64>,
     #[prost(int64, optional, tag = "2")]
     pub next: ::core::option::Option<i64>,
+    /// number of reservations matched by the filter, if asked by total_mode
     #[prost(int64, optional, tag = "3")]
     pub total: ::core::option::Option<i64>,
+    /// opaque cursors of the previous and next page, empty if there is no such page
+    #[prost(string, tag = "4")]
+    pub prev_cursor: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub next_cursor: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterResponse {
     #[prost(message, repeated, tag = "1")]
@@ -180,19 +385,238 @@ pub struct FilterResponse {
     #[prost(message, optional, tag = "2")]
     pub pager: ::core::option::Option<FilterPager>,
 }
-/// Client can listen reservation updates by sending a ListenRequest
+/// Client can listen reservation updates by sending a ListenRequest. The filters are checked
+/// against the reservation of the change, for a DELETE that is the reservation before the delete
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListenRequest {}
+pub struct ListenRequest {
+    /// if set, changes after this change id will be sent first, then the new ones.
+    /// If not set, only the changes made after listening will be sent
+    #[prost(int64, optional, tag = "1")]
+    pub since_change_id: ::core::option::Option<i64>,
+    /// only listen to changes for this resource. If empty, listen to all resources
+    #[prost(string, tag = "2")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// only listen to changes for resources with this prefix, e.g. "ixia-". If empty, no prefix is required
+    #[prost(string, tag = "3")]
+    pub resource_prefix: ::prost::alloc::string::String,
+    /// only listen to changes for this user. If empty, listen to all users
+    #[prost(string, tag = "4")]
+    pub user_id: ::prost::alloc::string::String,
+    /// only listen to changes for reservations in these status. If empty, listen to all status
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "5")]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// only listen to these kinds of changes. If empty, listen to all kinds of changes
+    #[prost(enumeration = "ReservationUpdateType", repeated, tag = "6")]
+    pub ops: ::prost::alloc::vec::Vec<i32>,
+}
 /// Server will send ListenResponse to client in streaming response
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListenResponse {
     /// update tytpe
     #[prost(enumeration = "ReservationUpdateType", tag = "1")]
     pub op: i32,
-    /// reservation id for updated reservation
+    /// the reservation as the change left it, or as it was before a DELETE
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+    /// monotonically increasing change id, client could save it to resume listening later
+    #[prost(int64, tag = "3")]
+    pub change_id: i64,
+}
+/// one entry of the audit history of a reservation
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReservationChange {
+    /// change id, same as the change_id in ListenResponse
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(int64, tag = "2")]
+    pub reservation_id: i64,
+    #[prost(enumeration = "ReservationUpdateType", tag = "3")]
+    pub op: i32,
+    /// when the change was made
+    #[prost(message, optional, tag = "4")]
+    pub changed_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// who made the change, as claimed by the x-actor metadata of the request. It is not
+    /// authenticated, so it is informational only and must not be trusted. Empty if unknown
+    #[prost(string, tag = "5")]
+    pub actor: ::prost::alloc::string::String,
+    /// reservation before the change, not set for CREATE
+    #[prost(message, optional, tag = "6")]
+    pub old: ::core::option::Option<Reservation>,
+    /// reservation after the change, not set for DELETE
+    #[prost(message, optional, tag = "7")]
+    pub new: ::core::option::Option<Reservation>,
+}
+/// To get the audit history of a reservation, send a HistoryRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoryRequest {
+    #[prost(int64, tag = "1")]
+    pub reservation_id: i64,
+}
+/// changes of the reservation will be returned in HistoryResponse, oldest first
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoryResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub changes: ::prost::alloc::vec::Vec<ReservationChange>,
+}
+/// a time window of a resource
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeSlot {
+    /// resource id for the time window
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// start time of the window, if not set, the window has no start
     #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// end time of the window, if not set, the window has no end
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// To get the free time windows of a resource, send an AvailabilityRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityRequest {
+    /// resource id to check
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// start time of the search window
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// end time of the search window
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// only return the free windows not shorter than this. If not set, return all free windows
+    #[prost(message, optional, tag = "4")]
+    pub min_duration: ::core::option::Option<::prost_types::Duration>,
+}
+/// free time windows within the search window, ordered by start time
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub slots: ::prost::alloc::vec::Vec<TimeSlot>,
+}
+/// To find the earliest free time slot from a pool of resources, send a FindSlotRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindSlotRequest {
+    /// candidate resource ids
+    #[prost(string, repeated, tag = "1")]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// resources with this prefix are also candidates. Only resources that have been reserved before are known
+    #[prost(string, tag = "2")]
+    pub resource_prefix: ::prost::alloc::string::String,
+    /// how long the slot should be
+    #[prost(message, optional, tag = "3")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+    /// the slot should not start before this time
+    #[prost(message, optional, tag = "4")]
+    pub earliest_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// the slot should not end after this time. If not set, there's no limit
+    #[prost(message, optional, tag = "5")]
+    pub latest_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// if true, reserve the slot found for the user
+    #[prost(bool, tag = "6")]
+    pub reserve: bool,
+    /// user id for the reservation, required if reserve is true
+    #[prost(string, tag = "7")]
+    pub user_id: ::prost::alloc::string::String,
+    /// extra note for the reservation
+    #[prost(string, tag = "8")]
+    pub note: ::prost::alloc::string::String,
+}
+/// The earliest free slot found will be returned in FindSlotResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindSlotResponse {
+    #[prost(message, optional, tag = "1")]
+    pub slot: ::core::option::Option<TimeSlot>,
+    /// reservation made for the slot, only populated if reserve is true
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// a reservation conflicts with an existing one, also sent in the grpc status details of a
+/// conflict error
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConflictInfo {
+    /// time window of the reservation to make
+    #[prost(message, optional, tag = "1")]
+    pub new: ::core::option::Option<TimeSlot>,
+    /// time window of the existing reservation
+    #[prost(message, optional, tag = "2")]
+    pub old: ::core::option::Option<TimeSlot>,
+    /// the existing reservation blocking the new one, set in the details of a conflict error.
+    /// For reserve_many it could be another reservation of the same request, which has no id
+    #[prost(message, optional, tag = "3")]
+    pub existing: ::core::option::Option<Reservation>,
+    /// index of the reservation to make in a ReserveManyRequest
+    #[prost(uint32, tag = "4")]
+    pub index: u32,
+}
+/// free windows of the same duration on the same resource, nearest to the conflicting reservation
+/// first, sent in the grpc status details of a conflict error if asked
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SlotSuggestions {
+    #[prost(message, repeated, tag = "1")]
+    pub slots: ::prost::alloc::vec::Vec<TimeSlot>,
+}
+/// To check if a reservation could be made without making it, send a CheckRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckRequest {
+    #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+/// Conflicts found will be returned in CheckResponse. If empty, the reservation could be made
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub conflicts: ::prost::alloc::vec::Vec<ConflictInfo>,
+}
+/// To change many reservations at once, send a BulkRequest. All the reservations matched by the
+/// selector are changed in one transaction, the page fields of the selector are ignored.
+/// The selector must set at least one of resource_id, user_id, start, end or status(es)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulkRequest {
+    #[prost(enumeration = "BulkAction", tag = "3")]
+    pub action: i32,
+    /// the new note for BULK_ACTION_NOTE, or the cancel reason for BULK_ACTION_CANCEL
+    #[prost(string, tag = "4")]
+    pub note: ::prost::alloc::string::String,
+    /// if true, nothing is changed, the reservations are returned as they would be changed
+    #[prost(bool, tag = "5")]
+    pub dry_run: bool,
+    #[prost(oneof = "bulk_request::Selector", tags = "1, 2")]
+    pub selector: ::core::option::Option<bulk_request::Selector>,
+}
+/// Nested message and enum types in `BulkRequest`.
+pub mod bulk_request {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Selector {
+        #[prost(message, tag = "1")]
+        Filter(super::ReservationFilter),
+        #[prost(message, tag = "2")]
+        Query(super::ReservationQuery),
+    }
+}
+/// the reservations changed (or would be changed for a dry run) in BulkResponse, ordered by id.
+/// Reservations already in the target state are not included
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulkResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
 /// reservation status for a given time period
 #[derive(
     sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
@@ -203,6 +627,7 @@ pub enum ReservationStatus {
     Pending = 1,
     Confirmed = 2,
     Blocked = 3,
+    Cancelled = 4,
 }
 impl ReservationStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -215,6 +640,18 @@ impl ReservationStatus {
             ReservationStatus::Pending => "RESERVATION_STATUS_PENDING",
             ReservationStatus::Confirmed => "RESERVATION_STATUS_CONFIRMED",
             ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
+            ReservationStatus::Cancelled => "RESERVATION_STATUS_CANCELLED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_STATUS_UNKNOWN" => Some(Self::Unknown),
+            "RESERVATION_STATUS_PENDING" => Some(Self::Pending),
+            "RESERVATION_STATUS_CONFIRMED" => Some(Self::Confirmed),
+            "RESERVATION_STATUS_BLOCKED" => Some(Self::Blocked),
+            "RESERVATION_STATUS_CANCELLED" => Some(Self::Cancelled),
+            _ => None,
         }
     }
 }
@@ -240,6 +677,259 @@ impl ReservationUpdateType {
             ReservationUpdateType::Delete => "RESERVATION_UPDATE_TYPE_DELETE",
         }
     }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_UPDATE_TYPE_UNKNOWN" => Some(Self::Unknown),
+            "RESERVATION_UPDATE_TYPE_CREATE" => Some(Self::Create),
+            "RESERVATION_UPDATE_TYPE_UPDATE" => Some(Self::Update),
+            "RESERVATION_UPDATE_TYPE_DELETE" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+/// sort key for query and filter results, reservation id is always used to break ties
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationSortBy {
+    /// start time for query, reservation id for filter
+    Default = 0,
+    Id = 1,
+    Start = 2,
+    CreatedAt = 3,
+    UpdatedAt = 4,
+    End = 5,
+}
+impl ReservationSortBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationSortBy::Default => "RESERVATION_SORT_BY_DEFAULT",
+            ReservationSortBy::Id => "RESERVATION_SORT_BY_ID",
+            ReservationSortBy::Start => "RESERVATION_SORT_BY_START",
+            ReservationSortBy::CreatedAt => "RESERVATION_SORT_BY_CREATED_AT",
+            ReservationSortBy::UpdatedAt => "RESERVATION_SORT_BY_UPDATED_AT",
+            ReservationSortBy::End => "RESERVATION_SORT_BY_END",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_SORT_BY_DEFAULT" => Some(Self::Default),
+            "RESERVATION_SORT_BY_ID" => Some(Self::Id),
+            "RESERVATION_SORT_BY_START" => Some(Self::Start),
+            "RESERVATION_SORT_BY_CREATED_AT" => Some(Self::CreatedAt),
+            "RESERVATION_SORT_BY_UPDATED_AT" => Some(Self::UpdatedAt),
+            "RESERVATION_SORT_BY_END" => Some(Self::End),
+            _ => None,
+        }
+    }
+}
+/// how the timespan of a reservation is matched against the time window of a query
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationMatchMode {
+    /// the reservation is fully contained in the window
+    Contained = 0,
+    /// the reservation overlaps with the window
+    Overlapping = 1,
+    /// the reservation starts within the window
+    StartingWithin = 2,
+    /// the reservation ends within the window
+    EndingWithin = 3,
+}
+impl ReservationMatchMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationMatchMode::Contained => "RESERVATION_MATCH_MODE_CONTAINED",
+            ReservationMatchMode::Overlapping => "RESERVATION_MATCH_MODE_OVERLAPPING",
+            ReservationMatchMode::StartingWithin => "RESERVATION_MATCH_MODE_STARTING_WITHIN",
+            ReservationMatchMode::EndingWithin => "RESERVATION_MATCH_MODE_ENDING_WITHIN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_MATCH_MODE_CONTAINED" => Some(Self::Contained),
+            "RESERVATION_MATCH_MODE_OVERLAPPING" => Some(Self::Overlapping),
+            "RESERVATION_MATCH_MODE_STARTING_WITHIN" => Some(Self::StartingWithin),
+            "RESERVATION_MATCH_MODE_ENDING_WITHIN" => Some(Self::EndingWithin),
+            _ => None,
+        }
+    }
+}
+/// how the total number of matched reservations is counted for a filter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationTotalMode {
+    /// total is not counted
+    None = 0,
+    /// exact count of matched reservations, could be slow for large tables
+    Exact = 1,
+    /// estimate from the query planner, cheap but approximate
+    Estimate = 2,
+}
+impl ReservationTotalMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationTotalMode::None => "RESERVATION_TOTAL_MODE_NONE",
+            ReservationTotalMode::Exact => "RESERVATION_TOTAL_MODE_EXACT",
+            ReservationTotalMode::Estimate => "RESERVATION_TOTAL_MODE_ESTIMATE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_TOTAL_MODE_NONE" => Some(Self::None),
+            "RESERVATION_TOTAL_MODE_EXACT" => Some(Self::Exact),
+            "RESERVATION_TOTAL_MODE_ESTIMATE" => Some(Self::Estimate),
+            _ => None,
+        }
+    }
+}
+/// stable reason of an error, sent without the ERROR_REASON_ prefix in the google.rpc.ErrorInfo
+/// of the grpc status details
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ErrorReason {
+    Unspecified = 0,
+    ConfigReadError = 1,
+    ConfigParseError = 2,
+    DbError = 3,
+    InvalidTime = 4,
+    ConflictReservation = 5,
+    NotFound = 6,
+    NoAvailableSlot = 7,
+    InvalidReservationId = 8,
+    InvalidUserId = 9,
+    InvalidResourceId = 10,
+    InvalidPageSize = 11,
+    InvalidCursor = 12,
+    InvalidPageToken = 13,
+    InvalidSortBy = 14,
+    InvalidStatus = 15,
+    InvalidUpdateType = 16,
+    InvalidDuration = 17,
+    InvalidUpdateMask = 18,
+    InvalidTransition = 19,
+    VersionMismatch = 20,
+    Unknown = 21,
+    InvalidSuggestionCount = 22,
+    InvalidBulkAction = 23,
+    InvalidSelector = 24,
+}
+impl ErrorReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ErrorReason::Unspecified => "ERROR_REASON_UNSPECIFIED",
+            ErrorReason::ConfigReadError => "ERROR_REASON_CONFIG_READ_ERROR",
+            ErrorReason::ConfigParseError => "ERROR_REASON_CONFIG_PARSE_ERROR",
+            ErrorReason::DbError => "ERROR_REASON_DB_ERROR",
+            ErrorReason::InvalidTime => "ERROR_REASON_INVALID_TIME",
+            ErrorReason::ConflictReservation => "ERROR_REASON_CONFLICT_RESERVATION",
+            ErrorReason::NotFound => "ERROR_REASON_NOT_FOUND",
+            ErrorReason::NoAvailableSlot => "ERROR_REASON_NO_AVAILABLE_SLOT",
+            ErrorReason::InvalidReservationId => "ERROR_REASON_INVALID_RESERVATION_ID",
+            ErrorReason::InvalidUserId => "ERROR_REASON_INVALID_USER_ID",
+            ErrorReason::InvalidResourceId => "ERROR_REASON_INVALID_RESOURCE_ID",
+            ErrorReason::InvalidPageSize => "ERROR_REASON_INVALID_PAGE_SIZE",
+            ErrorReason::InvalidCursor => "ERROR_REASON_INVALID_CURSOR",
+            ErrorReason::InvalidPageToken => "ERROR_REASON_INVALID_PAGE_TOKEN",
+            ErrorReason::InvalidSortBy => "ERROR_REASON_INVALID_SORT_BY",
+            ErrorReason::InvalidStatus => "ERROR_REASON_INVALID_STATUS",
+            ErrorReason::InvalidUpdateType => "ERROR_REASON_INVALID_UPDATE_TYPE",
+            ErrorReason::InvalidDuration => "ERROR_REASON_INVALID_DURATION",
+            ErrorReason::InvalidUpdateMask => "ERROR_REASON_INVALID_UPDATE_MASK",
+            ErrorReason::InvalidTransition => "ERROR_REASON_INVALID_TRANSITION",
+            ErrorReason::VersionMismatch => "ERROR_REASON_VERSION_MISMATCH",
+            ErrorReason::Unknown => "ERROR_REASON_UNKNOWN",
+            ErrorReason::InvalidSuggestionCount => "ERROR_REASON_INVALID_SUGGESTION_COUNT",
+            ErrorReason::InvalidBulkAction => "ERROR_REASON_INVALID_BULK_ACTION",
+            ErrorReason::InvalidSelector => "ERROR_REASON_INVALID_SELECTOR",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ERROR_REASON_UNSPECIFIED" => Some(Self::Unspecified),
+            "ERROR_REASON_CONFIG_READ_ERROR" => Some(Self::ConfigReadError),
+            "ERROR_REASON_CONFIG_PARSE_ERROR" => Some(Self::ConfigParseError),
+            "ERROR_REASON_DB_ERROR" => Some(Self::DbError),
+            "ERROR_REASON_INVALID_TIME" => Some(Self::InvalidTime),
+            "ERROR_REASON_CONFLICT_RESERVATION" => Some(Self::ConflictReservation),
+            "ERROR_REASON_NOT_FOUND" => Some(Self::NotFound),
+            "ERROR_REASON_NO_AVAILABLE_SLOT" => Some(Self::NoAvailableSlot),
+            "ERROR_REASON_INVALID_RESERVATION_ID" => Some(Self::InvalidReservationId),
+            "ERROR_REASON_INVALID_USER_ID" => Some(Self::InvalidUserId),
+            "ERROR_REASON_INVALID_RESOURCE_ID" => Some(Self::InvalidResourceId),
+            "ERROR_REASON_INVALID_PAGE_SIZE" => Some(Self::InvalidPageSize),
+            "ERROR_REASON_INVALID_CURSOR" => Some(Self::InvalidCursor),
+            "ERROR_REASON_INVALID_PAGE_TOKEN" => Some(Self::InvalidPageToken),
+            "ERROR_REASON_INVALID_SORT_BY" => Some(Self::InvalidSortBy),
+            "ERROR_REASON_INVALID_STATUS" => Some(Self::InvalidStatus),
+            "ERROR_REASON_INVALID_UPDATE_TYPE" => Some(Self::InvalidUpdateType),
+            "ERROR_REASON_INVALID_DURATION" => Some(Self::InvalidDuration),
+            "ERROR_REASON_INVALID_UPDATE_MASK" => Some(Self::InvalidUpdateMask),
+            "ERROR_REASON_INVALID_TRANSITION" => Some(Self::InvalidTransition),
+            "ERROR_REASON_VERSION_MISMATCH" => Some(Self::VersionMismatch),
+            "ERROR_REASON_UNKNOWN" => Some(Self::Unknown),
+            "ERROR_REASON_INVALID_SUGGESTION_COUNT" => Some(Self::InvalidSuggestionCount),
+            "ERROR_REASON_INVALID_BULK_ACTION" => Some(Self::InvalidBulkAction),
+            "ERROR_REASON_INVALID_SELECTOR" => Some(Self::InvalidSelector),
+            _ => None,
+        }
+    }
+}
+/// the change a BulkRequest makes to every reservation selected
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum BulkAction {
+    Unknown = 0,
+    /// confirm the pending reservations
+    Confirm = 1,
+    /// cancel the reservations, with the note of the request as the reason
+    Cancel = 2,
+    /// set the note of the request on the reservations
+    Note = 3,
+}
+impl BulkAction {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BulkAction::Unknown => "BULK_ACTION_UNKNOWN",
+            BulkAction::Confirm => "BULK_ACTION_CONFIRM",
+            BulkAction::Cancel => "BULK_ACTION_CANCEL",
+            BulkAction::Note => "BULK_ACTION_NOTE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "BULK_ACTION_UNKNOWN" => Some(Self::Unknown),
+            "BULK_ACTION_CONFIRM" => Some(Self::Confirm),
+            "BULK_ACTION_CANCEL" => Some(Self::Cancel),
+            "BULK_ACTION_NOTE" => Some(Self::Note),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
 pub mod reservation_service_client {
@@ -326,7 +1016,25 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// confirm a pending reservation, if the reservation is not pending, do nothing
+        /// make several reservations in one transaction, all conflicts are reported if any
+        pub async fn reserve_many(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReserveManyRequest>,
+        ) -> Result<tonic::Response<super::ReserveManyResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/reserve_many",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// confirm a pending reservation, if the reservation is already confirmed, do nothing.
+        /// Confirming a reservation in any other status is an invalid transition
         pub async fn confirm(
             &mut self,
             request: impl tonic::IntoRequest<super::ConfirmRequest>,
@@ -342,10 +1050,26 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// update reservation note
+        /// move a reservation to another status
+        pub async fn transition(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TransitionRequest>,
+        ) -> Result<tonic::Response<super::TransitionResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/transition");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// update the masked fields of a reservation
         pub async fn update(
             &mut self,
-            request: impl tonic::IntoRequest<super::Reservation>,
+            request: impl tonic::IntoRequest<super::UpdateRequest>,
         ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
@@ -358,6 +1082,22 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// move a reservation to another time window or resource
+        pub async fn reschedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RescheduleRequest>,
+        ) -> Result<tonic::Response<super::RescheduleResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/reschedule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// cancel a reservation
         pub async fn cancel(
             &mut self,
@@ -428,7 +1168,7 @@ pub mod reservation_service_client {
         pub async fn listen(
             &mut self,
             request: impl tonic::IntoRequest<super::ListenRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::ListenResponse>>, tonic::Status>
         {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
@@ -443,13 +1183,94 @@ pub mod reservation_service_client {
                 .server_streaming(request.into_request(), path, codec)
                 .await
         }
-    }
-}
+        /// get the audit history of a reservation. The actor of a change is taken as is from the
+        /// x-actor metadata, it is informational only
+        pub async fn history(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HistoryRequest>,
+        ) -> Result<tonic::Response<super::HistoryResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/history");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// get free time windows of a resource
+        pub async fn availability(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/availability",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// find the earliest free slot from a pool of resources, and optionally reserve it
+        pub async fn find_slot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FindSlotRequest>,
+        ) -> Result<tonic::Response<super::FindSlotResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/find_slot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// check if a reservation could be made, without making it
+        pub async fn check(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckRequest>,
+        ) -> Result<tonic::Response<super::CheckResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/check");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// confirm, cancel or note all the reservations selected by a filter or query
+        pub async fn bulk(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BulkRequest>,
+        ) -> Result<tonic::Response<super::BulkResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/bulk");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
 /// Generated server implementations.
 pub mod reservation_service_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    ///Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
     #[async_trait]
     pub trait ReservationService: Send + Sync + 'static {
         /// make a reservation
@@ -457,16 +1278,32 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::ReserveRequest>,
         ) -> Result<tonic::Response<super::ReserveResponse>, tonic::Status>;
-        /// confirm a pending reservation, if the reservation is not pending, do nothing
+        /// make several reservations in one transaction, all conflicts are reported if any
+        async fn reserve_many(
+            &self,
+            request: tonic::Request<super::ReserveManyRequest>,
+        ) -> Result<tonic::Response<super::ReserveManyResponse>, tonic::Status>;
+        /// confirm a pending reservation, if the reservation is already confirmed, do nothing.
+        /// Confirming a reservation in any other status is an invalid transition
         async fn confirm(
             &self,
             request: tonic::Request<super::ConfirmRequest>,
         ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status>;
-        /// update reservation note
+        /// move a reservation to another status
+        async fn transition(
+            &self,
+            request: tonic::Request<super::TransitionRequest>,
+        ) -> Result<tonic::Response<super::TransitionResponse>, tonic::Status>;
+        /// update the masked fields of a reservation
         async fn update(
             &self,
-            request: tonic::Request<super::Reservation>,
+            request: tonic::Request<super::UpdateRequest>,
         ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
+        /// move a reservation to another time window or resource
+        async fn reschedule(
+            &self,
+            request: tonic::Request<super::RescheduleRequest>,
+        ) -> Result<tonic::Response<super::RescheduleResponse>, tonic::Status>;
         /// cancel a reservation
         async fn cancel(
             &self,
@@ -477,7 +1314,7 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::GetRequest>,
         ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
-        ///Server streaming response type for the query method.
+        /// Server streaming response type for the query method.
         type queryStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
             + Send
             + 'static;
@@ -491,8 +1328,8 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::FilterRequest>,
         ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
-        ///Server streaming response type for the listen method.
-        type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
+        /// Server streaming response type for the listen method.
+        type listenStream: futures_core::Stream<Item = Result<super::ListenResponse, tonic::Status>>
             + Send
             + 'static;
         /// another system could monitor newly added/confirmed/cancelled reservations
@@ -500,6 +1337,32 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::ListenRequest>,
         ) -> Result<tonic::Response<Self::listenStream>, tonic::Status>;
+        /// get the audit history of a reservation. The actor of a change is taken as is from the
+        /// x-actor metadata, it is informational only
+        async fn history(
+            &self,
+            request: tonic::Request<super::HistoryRequest>,
+        ) -> Result<tonic::Response<super::HistoryResponse>, tonic::Status>;
+        /// get free time windows of a resource
+        async fn availability(
+            &self,
+            request: tonic::Request<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status>;
+        /// find the earliest free slot from a pool of resources, and optionally reserve it
+        async fn find_slot(
+            &self,
+            request: tonic::Request<super::FindSlotRequest>,
+        ) -> Result<tonic::Response<super::FindSlotResponse>, tonic::Status>;
+        /// check if a reservation could be made, without making it
+        async fn check(
+            &self,
+            request: tonic::Request<super::CheckRequest>,
+        ) -> Result<tonic::Response<super::CheckResponse>, tonic::Status>;
+        /// confirm, cancel or note all the reservations selected by a filter or query
+        async fn bulk(
+            &self,
+            request: tonic::Request<super::BulkRequest>,
+        ) -> Result<tonic::Response<super::BulkResponse>, tonic::Status>;
     }
     /// Reservation service
     #[derive(Debug)]
@@ -586,6 +1449,40 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/reserve_many" => {
+                    #[allow(non_camel_case_types)]
+                    struct reserve_manySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveManyRequest>
+                        for reserve_manySvc<T>
+                    {
+                        type Response = super::ReserveManyResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReserveManyRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reserve_many(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = reserve_manySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/reservation.ReservationService/confirm" => {
                     #[allow(non_camel_case_types)]
                     struct confirmSvc<T: ReservationService>(pub Arc<T>);
@@ -617,15 +1514,48 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/transition" => {
+                    #[allow(non_camel_case_types)]
+                    struct transitionSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::TransitionRequest> for transitionSvc<T>
+                    {
+                        type Response = super::TransitionResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TransitionRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).transition(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = transitionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/reservation.ReservationService/update" => {
                     #[allow(non_camel_case_types)]
                     struct updateSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::Reservation> for updateSvc<T> {
+                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
                         type Response = super::UpdateResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::Reservation>,
+                            request: tonic::Request<super::UpdateRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move { (*inner).update(request).await };
@@ -648,6 +1578,39 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/reschedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct rescheduleSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::RescheduleRequest> for rescheduleSvc<T>
+                    {
+                        type Response = super::RescheduleResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RescheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reschedule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = rescheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/reservation.ReservationService/cancel" => {
                     #[allow(non_camel_case_types)]
                     struct cancelSvc<T: ReservationService>(pub Arc<T>);
@@ -783,7 +1746,7 @@ pub mod reservation_service_server {
                         tonic::server::ServerStreamingService<super::ListenRequest>
                         for listenSvc<T>
                     {
-                        type Response = super::Reservation;
+                        type Response = super::ListenResponse;
                         type ResponseStream = T::listenStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
@@ -812,6 +1775,166 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/history" => {
+                    #[allow(non_camel_case_types)]
+                    struct historySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::HistoryRequest> for historySvc<T> {
+                        type Response = super::HistoryResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HistoryRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).history(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = historySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/availability" => {
+                    #[allow(non_camel_case_types)]
+                    struct availabilitySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::AvailabilityRequest>
+                        for availabilitySvc<T>
+                    {
+                        type Response = super::AvailabilityResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AvailabilityRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).availability(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = availabilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/find_slot" => {
+                    #[allow(non_camel_case_types)]
+                    struct find_slotSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::FindSlotRequest>
+                        for find_slotSvc<T>
+                    {
+                        type Response = super::FindSlotResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FindSlotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).find_slot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = find_slotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/check" => {
+                    #[allow(non_camel_case_types)]
+                    struct checkSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::CheckRequest> for checkSvc<T> {
+                        type Response = super::CheckResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CheckRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).check(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = checkSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/bulk" => {
+                    #[allow(non_camel_case_types)]
+                    struct bulkSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::BulkRequest> for bulkSvc<T> {
+                        type Response = super::BulkResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BulkRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).bulk(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = bulkSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
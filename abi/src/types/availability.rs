@@ -0,0 +1,124 @@
+use crate::{
+    AvailabilityRequest, Error, FindSlotRequest, Reservation, ReservationStatus, TimeSlot,
+    Validator,
+};
+use prost_types::Duration;
+
+use super::validate_range;
+
+impl Validator for AvailabilityRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+        validate_range(self.start.as_ref(), self.end.as_ref())?;
+        if let Some(d) = self.min_duration.as_ref() {
+            validate_duration(d)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validator for FindSlotRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_ids.is_empty() && self.resource_prefix.is_empty() {
+            return Err(Error::InvalidResourceId("".into()));
+        }
+        if let Some(rid) = self.resource_ids.iter().find(|rid| rid.is_empty()) {
+            return Err(Error::InvalidResourceId(rid.clone()));
+        }
+        match self.duration.as_ref() {
+            Some(d) if d.seconds > 0 || d.nanos > 0 => validate_duration(d)?,
+            _ => return Err(Error::InvalidDuration),
+        }
+        if self.earliest_start.is_none() {
+            return Err(Error::InvalidTime);
+        }
+        if self.latest_end.is_some() {
+            validate_range(self.earliest_start.as_ref(), self.latest_end.as_ref())?;
+        }
+        if self.reserve && self.user_id.is_empty() {
+            return Err(Error::InvalidUserId(self.user_id.clone()));
+        }
+        Ok(())
+    }
+}
+
+impl FindSlotRequest {
+    /// the pending reservation to make for the slot found
+    pub fn to_reservation(&self, slot: &TimeSlot) -> Reservation {
+        Reservation {
+            user_id: self.user_id.clone(),
+            resource_id: slot.resource_id.clone(),
+            start: slot.start.clone(),
+            end: slot.end.clone(),
+            note: self.note.clone(),
+            status: ReservationStatus::Pending as i32,
+            ..Default::default()
+        }
+    }
+}
+
+fn validate_duration(d: &Duration) -> Result<(), Error> {
+    if d.seconds < 0 || d.nanos < 0 {
+        return Err(Error::InvalidDuration);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn availability_request_should_be_validated() {
+        let mut req = AvailabilityRequest {
+            resource_id: "ixia-3230".into(),
+            start: "2022-12-26T15:00:00-0700".parse().ok(),
+            end: "2022-12-30T12:00:00-0700".parse().ok(),
+            min_duration: None,
+        };
+        assert!(req.validate().is_ok());
+
+        req.min_duration = Some(Duration {
+            seconds: -1,
+            nanos: 0,
+        });
+        assert_eq!(req.validate(), Err(Error::InvalidDuration));
+
+        req.min_duration = None;
+        req.end = None;
+        assert_eq!(req.validate(), Err(Error::InvalidTime));
+
+        req.resource_id = "".into();
+        assert_eq!(req.validate(), Err(Error::InvalidResourceId("".into())));
+    }
+
+    #[test]
+    fn find_slot_request_should_be_validated() {
+        let mut req = FindSlotRequest {
+            resource_ids: vec!["ixia-3230".into()],
+            duration: Some(Duration {
+                seconds: 3600,
+                nanos: 0,
+            }),
+            earliest_start: "2022-12-26T15:00:00-0700".parse().ok(),
+            ..Default::default()
+        };
+        assert!(req.validate().is_ok());
+
+        req.reserve = true;
+        assert_eq!(req.validate(), Err(Error::InvalidUserId("".into())));
+
+        req.reserve = false;
+        req.latest_end = "2022-12-26T14:00:00-0700".parse().ok();
+        assert_eq!(req.validate(), Err(Error::InvalidTime));
+
+        req.latest_end = None;
+        req.duration = Some(Duration::default());
+        assert_eq!(req.validate(), Err(Error::InvalidDuration));
+
+        req.resource_ids = vec![];
+        assert_eq!(req.validate(), Err(Error::InvalidResourceId("".into())));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    bulk_request::Selector, BulkAction, BulkRequest, Error, ReservationFilter, ReservationQuery,
+    Validator,
+};
+
+use super::validate_range;
+
+impl BulkRequest {
+    pub fn new(selector: impl Into<Selector>, action: BulkAction) -> Self {
+        Self {
+            selector: Some(selector.into()),
+            action: action as i32,
+            note: String::new(),
+            dry_run: false,
+        }
+    }
+
+    pub fn get_action(&self) -> Result<BulkAction, Error> {
+        match BulkAction::from_i32(self.action) {
+            Some(BulkAction::Unknown) | None => Err(Error::InvalidBulkAction(self.action)),
+            Some(action) => Ok(action),
+        }
+    }
+}
+
+impl From<ReservationFilter> for Selector {
+    fn from(filter: ReservationFilter) -> Self {
+        Selector::Filter(filter)
+    }
+}
+
+impl From<ReservationQuery> for Selector {
+    fn from(query: ReservationQuery) -> Self {
+        Selector::Query(query)
+    }
+}
+
+impl Validator for BulkRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.get_action()?;
+        let selective = match self.selector.as_ref() {
+            // a selector is not paged, only the fields selecting the reservations matter
+            Some(Selector::Filter(filter)) => {
+                filter.validate_selection()?;
+                !filter.resource_id.is_empty()
+                    || !filter.user_id.is_empty()
+                    || filter.start.is_some()
+                    || filter.end.is_some()
+                    || !filter.get_statuses().is_empty()
+            }
+            Some(Selector::Query(query)) => {
+                let statuses = query.get_statuses()?;
+                // either end of the query could be open
+                if let (Some(start), Some(end)) = (query.start.as_ref(), query.end.as_ref()) {
+                    validate_range(Some(start), Some(end))?;
+                }
+                !query.resource_id.is_empty()
+                    || !query.user_id.is_empty()
+                    || query.start.is_some()
+                    || query.end.is_some()
+                    || !statuses.is_empty()
+            }
+            None => false,
+        };
+        // an empty selector would change every reservation
+        if !selective {
+            return Err(Error::InvalidSelector);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ReservationFilterBuilder, ReservationQueryBuilder};
+
+    #[test]
+    fn bulk_request_should_be_validated() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("ixia-3230")
+            .build()
+            .unwrap();
+        let mut req = BulkRequest::new(filter, BulkAction::Cancel);
+        assert!(req.validate().is_ok());
+
+        // page fields are not used to select, so they are not checked
+        let filter = ReservationFilter {
+            resource_id: "ixia-3230".into(),
+            ..Default::default()
+        };
+        assert!(BulkRequest::new(filter, BulkAction::Cancel)
+            .validate()
+            .is_ok());
+
+        req.action = BulkAction::Unknown as i32;
+        assert_eq!(req.validate(), Err(Error::InvalidBulkAction(0)));
+        req.action = 42;
+        assert_eq!(req.validate(), Err(Error::InvalidBulkAction(42)));
+
+        req.action = BulkAction::Confirm as i32;
+        req.selector = None;
+        assert_eq!(req.validate(), Err(Error::InvalidSelector));
+
+        // a selector without any selecting field would match every reservation
+        let filter = ReservationFilterBuilder::default()
+            .include_cancelled(true)
+            .build()
+            .unwrap();
+        req.selector = Some(filter.into());
+        assert_eq!(req.validate(), Err(Error::InvalidSelector));
+        req.selector = Some(ReservationQuery::default().into());
+        assert_eq!(req.validate(), Err(Error::InvalidSelector));
+
+        let query = ReservationQueryBuilder::default()
+            .statuses(vec![42])
+            .build()
+            .unwrap();
+        req.selector = Some(query.into());
+        assert_eq!(req.validate(), Err(Error::InvalidStatus(42)));
+
+        let query = ReservationQueryBuilder::default()
+            .start(
+                "2022-12-26T15:00:00-0700"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        req.selector = Some(query.into());
+        assert!(req.validate().is_ok());
+
+        let query = ReservationQueryBuilder::default()
+            .start(
+                "2022-12-30T12:00:00-0700"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .end(
+                "2022-12-26T15:00:00-0700"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        req.selector = Some(query.into());
+        assert_eq!(req.validate(), Err(Error::InvalidTime));
+    }
+}
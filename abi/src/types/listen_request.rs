@@ -0,0 +1,120 @@
+use crate::{
+    Error, ListenRequest, ListenResponse, ReservationStatus, ReservationUpdateType, Validator,
+};
+
+impl Validator for ListenRequest {
+    fn validate(&self) -> Result<(), Error> {
+        for status in &self.statuses {
+            ReservationStatus::from_i32(*status).ok_or(Error::InvalidStatus(*status))?;
+        }
+        for op in &self.ops {
+            ReservationUpdateType::from_i32(*op).ok_or(Error::InvalidUpdateType(*op))?;
+        }
+        Ok(())
+    }
+}
+
+impl ListenRequest {
+    /// check if the change should be sent to the listener
+    pub fn matches(&self, change: &ListenResponse) -> bool {
+        if !self.ops.is_empty() && !self.ops.contains(&change.op) {
+            return false;
+        }
+        // a deleted reservation is checked as it was before the delete
+        let rsvp = match change.reservation.as_ref() {
+            Some(rsvp) => rsvp,
+            None => return true,
+        };
+
+        (self.resource_id.is_empty() || self.resource_id == rsvp.resource_id)
+            && rsvp.resource_id.starts_with(&self.resource_prefix)
+            && (self.user_id.is_empty() || self.user_id == rsvp.user_id)
+            && (self.statuses.is_empty() || self.statuses.contains(&rsvp.status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reservation;
+
+    fn change(op: ReservationUpdateType) -> ListenResponse {
+        let rsvp = Reservation::new_pending(
+            "tyr",
+            "ixia-3230",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "test device reservation",
+        );
+        ListenResponse {
+            op: op as i32,
+            reservation: Some(rsvp),
+            change_id: 1,
+        }
+    }
+
+    #[test]
+    fn empty_listen_request_should_match_all() {
+        let req = ListenRequest::default();
+        assert!(req.matches(&change(ReservationUpdateType::Create)));
+        assert!(req.matches(&change(ReservationUpdateType::Update)));
+    }
+
+    #[test]
+    fn listen_request_should_match_by_fields() {
+        let change = change(ReservationUpdateType::Create);
+        let req = |f: fn(&mut ListenRequest)| {
+            let mut req = ListenRequest::default();
+            f(&mut req);
+            req
+        };
+
+        assert!(req(|r| r.resource_id = "ixia-3230".into()).matches(&change));
+        assert!(!req(|r| r.resource_id = "ixia-3231".into()).matches(&change));
+        assert!(req(|r| r.resource_prefix = "ixia-".into()).matches(&change));
+        assert!(!req(|r| r.resource_prefix = "router-".into()).matches(&change));
+        assert!(req(|r| r.user_id = "tyr".into()).matches(&change));
+        assert!(!req(|r| r.user_id = "alice".into()).matches(&change));
+        assert!(req(|r| r.statuses = vec![ReservationStatus::Pending as i32]).matches(&change));
+        assert!(!req(|r| r.statuses = vec![ReservationStatus::Confirmed as i32]).matches(&change));
+        assert!(req(|r| r.ops = vec![ReservationUpdateType::Create as i32]).matches(&change));
+        assert!(!req(|r| r.ops = vec![ReservationUpdateType::Update as i32]).matches(&change));
+    }
+
+    #[test]
+    fn delete_change_should_be_filtered_like_others() {
+        let change = change(ReservationUpdateType::Delete);
+        let mut req = ListenRequest {
+            resource_id: "ixia-3230".into(),
+            user_id: "tyr".into(),
+            ..Default::default()
+        };
+        assert!(req.matches(&change));
+
+        req.user_id = "alice".into();
+        assert!(!req.matches(&change));
+
+        req.user_id = "tyr".into();
+        req.statuses = vec![ReservationStatus::Confirmed as i32];
+        assert!(!req.matches(&change));
+
+        req.statuses = vec![];
+        req.ops = vec![ReservationUpdateType::Create as i32];
+        assert!(!req.matches(&change));
+    }
+
+    #[test]
+    fn listen_request_with_invalid_enum_should_be_rejected() {
+        let req = ListenRequest {
+            statuses: vec![100],
+            ..Default::default()
+        };
+        assert_eq!(req.validate(), Err(Error::InvalidStatus(100)));
+
+        let req = ListenRequest {
+            ops: vec![100],
+            ..Default::default()
+        };
+        assert_eq!(req.validate(), Err(Error::InvalidUpdateType(100)));
+    }
+}
@@ -1,8 +1,19 @@
+mod availability;
+mod bulk_request;
+mod listen_request;
 mod request;
+mod reschedule;
 mod reservation;
 mod reservation_filter;
+mod reservation_match_mode;
 mod reservation_query;
+mod reservation_sort_by;
 mod reservation_status;
+mod reservation_update_type;
+mod reserve_request;
+mod time_range;
+mod time_slot;
+mod update_request;
 
 use std::ops::Bound;
 
@@ -35,6 +46,50 @@ pub fn get_timespan(start: Option<&Timestamp>, end: Option<&Timestamp>) -> PgRan
     }
 }
 
+/// version of the token format, tokens of other versions are rejected
+const TOKEN_VERSION: u8 = 1;
+
+/// encode a cursor into an opaque token
+pub(crate) fn encode_token(s: &str) -> String {
+    std::iter::once(TOKEN_VERSION)
+        .chain(s.bytes())
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// decode an opaque token, None if it is not a valid token
+pub(crate) fn decode_token(s: &str) -> Option<String> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    match bytes.split_first() {
+        Some((&TOKEN_VERSION, cursor)) => String::from_utf8(cursor.to_vec()).ok(),
+        _ => None,
+    }
+}
+
+pub(crate) struct NaiveRange<T> {
+    pub start: Option<T>,
+    pub end: Option<T>,
+}
+
+impl<T> From<PgRange<T>> for NaiveRange<T> {
+    fn from(r: PgRange<T>) -> Self {
+        let f = |b| match b {
+            Bound::Included(v) => Some(v),
+            Bound::Excluded(v) => Some(v),
+            Bound::Unbounded => None,
+        };
+        let start = f(r.start);
+        let end = f(r.end);
+        Self { start, end }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use prost_types::Timestamp;
@@ -1,6 +1,6 @@
 use crate::{
-    CancelRequest, ConfirmRequest, FilterRequest, GetRequest, QueryRequest, Reservation,
-    ReservationFilter, ReservationQuery, ReserveRequest, UpdateRequest,
+    CancelRequest, ConfirmRequest, FilterRequest, GetRequest, ListenRequest, QueryRequest,
+    ReservationFilter, ReservationQuery,
 };
 
 macro_rules! impl_new {
@@ -13,24 +13,44 @@ macro_rules! impl_new {
             }
         }
     };
-    ($name:ident) => {
-        impl $name {
-            pub fn new(id: i64) -> Self {
-                Self { id }
-            }
-        }
-    };
 }
 
-impl_new!(ReserveRequest, reservation, Reservation);
 impl_new!(FilterRequest, filter, ReservationFilter);
 impl_new!(QueryRequest, query, ReservationQuery);
-impl_new!(ConfirmRequest);
-impl_new!(GetRequest);
-impl_new!(CancelRequest);
 
-impl UpdateRequest {
-    pub fn new(id: i64, note: String) -> Self {
-        Self { id, note }
+impl ConfirmRequest {
+    pub fn new(id: i64) -> Self {
+        Self {
+            id,
+            expected_version: None,
+        }
+    }
+}
+
+impl GetRequest {
+    pub fn new(id: i64) -> Self {
+        Self {
+            id,
+            include_cancelled: false,
+        }
+    }
+}
+
+impl CancelRequest {
+    pub fn new(id: i64, reason: impl Into<String>) -> Self {
+        Self {
+            id,
+            reason: reason.into(),
+            expected_version: None,
+        }
+    }
+}
+
+impl ListenRequest {
+    pub fn new(since_change_id: i64) -> Self {
+        Self {
+            since_change_id: Some(since_change_id),
+            ..Default::default()
+        }
     }
 }
@@ -0,0 +1,33 @@
+use crate::{convert_to_timestamp, Error, RescheduleRequest, Validator};
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::postgres::types::PgRange;
+
+use super::{get_timespan, validate_range};
+
+impl RescheduleRequest {
+    pub fn new(
+        id: i64,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        rid: impl Into<String>,
+    ) -> Self {
+        Self {
+            id,
+            start: Some(convert_to_timestamp(start.with_timezone(&Utc))),
+            end: Some(convert_to_timestamp(end.with_timezone(&Utc))),
+            resource_id: rid.into(),
+        }
+    }
+
+    pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
+        get_timespan(self.start.as_ref(), self.end.as_ref())
+    }
+}
+
+impl Validator for RescheduleRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.id.validate()?;
+        validate_range(self.start.as_ref(), self.end.as_ref())?;
+        Ok(())
+    }
+}
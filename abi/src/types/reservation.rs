@@ -1,13 +1,14 @@
-use crate::{convert_to_timestamp, Error, Reservation, ReservationStatus, RsvpStatus, Validator};
+use crate::{
+    convert_to_timestamp, convert_to_utc_time, Error, Reservation, ReservationStatus,
+    ReservationWindow, RsvpStatus, Validator,
+};
 use chrono::{DateTime, FixedOffset, Utc};
 use sqlx::{
     postgres::{types::PgRange, PgRow},
     FromRow, Row,
 };
 
-use std::ops::Bound;
-
-use super::{get_timespan, validate_range};
+use super::{get_timespan, validate_range, NaiveRange};
 
 impl Reservation {
     pub fn new_pending(
@@ -25,12 +26,31 @@ impl Reservation {
             end: Some(convert_to_timestamp(end.with_timezone(&Utc))),
             note: note.into(),
             status: ReservationStatus::Pending as i32,
+            ..Default::default()
+        }
+    }
+
+    /// fail if the reservation has been changed since the expected version was read
+    pub fn check_version(&self, expected: Option<i64>) -> Result<(), Error> {
+        match expected {
+            Some(version) if version != self.version => {
+                Err(Error::VersionMismatch(version, self.version))
+            }
+            _ => Ok(()),
         }
     }
 
     pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    pub fn get_window(&self) -> ReservationWindow {
+        ReservationWindow {
+            rid: self.resource_id.clone(),
+            start: convert_to_utc_time(self.start.clone().unwrap()),
+            end: convert_to_utc_time(self.end.clone().unwrap()),
+        }
+    }
 }
 
 impl Validator for Reservation {
@@ -58,6 +78,10 @@ impl FromRow<'_, PgRow> for Reservation {
         let start = range.start.unwrap();
         let end = range.end.unwrap();
         let status: RsvpStatus = row.get("status");
+        let cancelled_at: Option<DateTime<Utc>> = row.get("cancelled_at");
+        let cancel_reason: Option<String> = row.get("cancel_reason");
+        let created_at: DateTime<Utc> = row.get("created_at");
+        let updated_at: DateTime<Utc> = row.get("updated_at");
         Ok(Self {
             id,
             user_id: row.get("user_id"),
@@ -66,22 +90,11 @@ impl FromRow<'_, PgRow> for Reservation {
             end: Some(convert_to_timestamp(end)),
             note: row.get("note"),
             status: ReservationStatus::from(status) as i32,
+            cancelled_at: cancelled_at.map(convert_to_timestamp),
+            cancel_reason: cancel_reason.unwrap_or_default(),
+            created_at: Some(convert_to_timestamp(created_at)),
+            updated_at: Some(convert_to_timestamp(updated_at)),
+            version: row.get("version"),
         })
     }
 }
-struct NaiveRange<T> {
-    start: Option<T>,
-    end: Option<T>,
-}
-impl<T> From<PgRange<T>> for NaiveRange<T> {
-    fn from(r: PgRange<T>) -> Self {
-        let f = |b| match b {
-            Bound::Included(v) => Some(v),
-            Bound::Excluded(v) => Some(v),
-            Bound::Unbounded => None,
-        };
-        let start = f(r.start);
-        let end = f(r.end);
-        Self { start, end }
-    }
-}
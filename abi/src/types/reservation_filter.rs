@@ -1,10 +1,28 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, ops::Bound};
 
 use crate::{
     Error, FilterPager, Normalizer, Reservation, ReservationFilter, ReservationFilterBuilder,
-    ReservationStatus, ToSql, Validator,
+    ReservationMatchMode, ReservationSortBy, ReservationStatus, TimeRange, ToSql, Validator,
 };
 
+use super::{
+    decode_token, encode_token,
+    reservation_status::{merge_statuses, push_status_condition, validate_statuses},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use prost_types::Timestamp;
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
+
+/// decoded opaque cursor of a filter
+#[derive(Debug, Clone, Copy)]
+struct FilterCursor {
+    /// if true, the page is the rows before the cursor, otherwise the rows after it
+    backward: bool,
+    /// value of the sort key, None if sorted by id
+    key: Option<DateTime<Utc>>,
+    id: i64,
+}
+
 impl ReservationFilterBuilder {
     pub fn build(&self) -> Result<ReservationFilter, Error> {
         let mut filter = self
@@ -26,33 +44,63 @@ impl Validator for ReservationFilter {
             }
         }
 
-        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
-        Ok(())
+        self.get_page_cursor()?;
+        self.validate_selection()
     }
 }
 
 impl Normalizer for ReservationFilter {
     fn do_normalize(&mut self) {
-        if self.status == ReservationStatus::Unknown as i32 {
-            self.status = ReservationStatus::Pending as i32;
+        self.statuses = self.get_statuses().into_iter().map(|s| s as i32).collect();
+        self.note = self.note.trim().to_string();
+        if ReservationSortBy::from_i32(self.sort_by).unwrap_or_default()
+            == ReservationSortBy::Default
+        {
+            self.sort_by = ReservationSortBy::Id as i32;
         }
     }
 }
 
 impl ReservationFilter {
-    pub fn get_pager(&self, data: &mut VecDeque<Reservation>) -> Result<FilterPager, Error> {
-        let has_prev = self.cursor.is_some();
-        let start = if has_prev { data.pop_front() } else { None };
+    pub fn get_pager(
+        &self,
+        data: &mut VecDeque<Reservation>,
+        total: Option<i64>,
+    ) -> Result<FilterPager, Error> {
+        let cursor = self.get_page_cursor()?;
+        // the id cursor row is included in the result, it is the prev of the page
+        let start = match cursor {
+            None if self.cursor.is_some() => data.pop_front(),
+            _ => None,
+        };
 
-        let has_next = data.len() as i64 > self.page_size;
-        let end = if has_next { data.pop_back() } else { None };
+        let has_more = data.len() as i64 > self.page_size;
+        let end = if has_more { data.pop_back() } else { None };
 
-        let pager = FilterPager {
-            prev: start.map(|r| r.id),
-            next: end.map(|r| r.id),
+        // rows before a backward cursor are read in reverse order
+        let backward = cursor.as_ref().is_some_and(|c| c.backward);
+        if backward {
+            data.make_contiguous().reverse();
+        }
+        let has_prev = if backward {
+            has_more
+        } else {
+            cursor.is_some() || start.is_some()
+        };
+        let has_next = backward || has_more;
 
-            // TODO: how to get total efficiently?
-            total: None,
+        let pager = FilterPager {
+            prev: start.filter(|_| cursor.is_none()).map(|r| r.id),
+            next: end.filter(|_| cursor.is_none()).map(|r| r.id),
+            total,
+            prev_cursor: match data.front() {
+                Some(r) if has_prev => self.encode_cursor(r, true),
+                _ => String::new(),
+            },
+            next_cursor: match data.back() {
+                Some(r) if has_next => self.encode_cursor(r, false),
+                _ => String::new(),
+            },
         };
         Ok(pager)
     }
@@ -63,41 +111,275 @@ impl ReservationFilter {
     pub fn get_status(&self) -> ReservationStatus {
         ReservationStatus::from_i32(self.status).unwrap()
     }
+
+    /// validate the fields selecting the reservations, the page fields are not checked
+    pub fn validate_selection(&self) -> Result<(), Error> {
+        validate_statuses(self.status, &self.statuses)?;
+        if let Some(created) = self.created.as_ref() {
+            created.validate()?;
+        }
+        if let Some(updated) = self.updated.as_ref() {
+            updated.validate()?;
+        }
+        self.get_window().validate()
+    }
+
+    /// all the statuses to match, empty means any status
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        merge_statuses(self.status, &self.statuses)
+    }
+
+    /// sql expression of the sort key
+    fn sort_column(&self) -> &'static str {
+        match ReservationSortBy::from_i32(self.sort_by).unwrap_or_default() {
+            ReservationSortBy::Default | ReservationSortBy::Id => "id",
+            ReservationSortBy::Start => "lower(timespan)",
+            ReservationSortBy::CreatedAt => "created_at",
+            ReservationSortBy::UpdatedAt => "updated_at",
+            ReservationSortBy::End => "upper(timespan)",
+        }
+    }
+
+    fn get_sort_by(&self) -> ReservationSortBy {
+        match ReservationSortBy::from_i32(self.sort_by).unwrap_or_default() {
+            ReservationSortBy::Default => ReservationSortBy::Id,
+            sort_by => sort_by,
+        }
+    }
+
+    /// value of the sort key of the reservation, None if sorted by id
+    fn sort_key(&self, rsvp: &Reservation) -> Option<Timestamp> {
+        match self.get_sort_by() {
+            ReservationSortBy::Default | ReservationSortBy::Id => None,
+            ReservationSortBy::Start => rsvp.start.clone(),
+            ReservationSortBy::End => rsvp.end.clone(),
+            ReservationSortBy::CreatedAt => rsvp.created_at.clone(),
+            ReservationSortBy::UpdatedAt => rsvp.updated_at.clone(),
+        }
+    }
+
+    /// opaque cursor of the rows before (backward) or after the reservation, in the sort order
+    fn encode_cursor(&self, rsvp: &Reservation, backward: bool) -> String {
+        let key = self.sort_key(rsvp).unwrap_or_default();
+        encode_token(&format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.get_sort_by() as i32,
+            self.desc as u8,
+            backward as u8,
+            key.seconds,
+            key.nanos,
+            rsvp.id
+        ))
+    }
+
+    /// decode the opaque cursor, it must be returned for the same sort key and direction
+    fn get_page_cursor(&self) -> Result<Option<FilterCursor>, Error> {
+        if self.page_cursor.is_empty() {
+            return Ok(None);
+        }
+        let invalid = || Error::InvalidPageToken(self.page_cursor.clone());
+        let cursor = decode_token(&self.page_cursor).ok_or_else(invalid)?;
+        let parts = cursor
+            .split(':')
+            .map(|p| p.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        match parts[..] {
+            [sort_by, desc, backward, seconds, nanos, id]
+                if sort_by == self.get_sort_by() as i64
+                    && desc == self.desc as i64
+                    && (0..=1).contains(&backward)
+                    && (0..1_000_000_000).contains(&nanos)
+                    && id > 0 =>
+            {
+                // the cursor could be forged, so the time may be out of range
+                let key = match self.get_sort_by() {
+                    ReservationSortBy::Default | ReservationSortBy::Id => None,
+                    _ => Some(
+                        Utc.timestamp_opt(seconds, nanos as u32)
+                            .single()
+                            .ok_or_else(invalid)?,
+                    ),
+                };
+                Ok(Some(FilterCursor {
+                    backward: backward == 1,
+                    key,
+                    id,
+                }))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// push the condition of rows from the cursor on, in the sort order. The cursor row is
+    /// included, it is the prev of the page
+    fn push_cursor_condition(&self, builder: &mut QueryBuilder<'static, Postgres>) {
+        // the opaque cursor row is not included, the page starts after or ends before it
+        if let Ok(Some(cursor)) = self.get_page_cursor() {
+            let op = if self.desc != cursor.backward {
+                "<"
+            } else {
+                ">"
+            };
+            match cursor.key {
+                None => {
+                    builder.push(format!("id {} ", op)).push_bind(cursor.id);
+                }
+                Some(key) => {
+                    builder
+                        .push(format!("({}, id) {} (", self.sort_column(), op))
+                        .push_bind(key)
+                        .push(", ")
+                        .push_bind(cursor.id)
+                        .push(")");
+                }
+            }
+            return;
+        }
+        let op = if self.desc { "<=" } else { ">=" };
+        match (self.sort_column(), self.cursor) {
+            ("id", _) => {
+                builder
+                    .push(format!("id {} ", op))
+                    .push_bind(self.get_cursor());
+            }
+            (column, Some(cursor)) => {
+                builder
+                    .push(format!(
+                        "({column}, id) {op} (SELECT {column}, id FROM rsvp.reservations WHERE id = "
+                    ))
+                    .push_bind(cursor)
+                    .push(")");
+            }
+            (_, None) => {
+                builder.push("TRUE");
+            }
+        }
+    }
+
+    /// push the conditions on status, user, resource and timestamps
+    fn push_match_conditions(&self, builder: &mut QueryBuilder<'static, Postgres>) {
+        push_status_condition(builder, &self.get_statuses(), self.include_cancelled);
+        if !self.user_id.is_empty() {
+            builder
+                .push(" AND user_id = ")
+                .push_bind(self.user_id.clone());
+        }
+        if !self.resource_id.is_empty() {
+            builder
+                .push(" AND resource_id = ")
+                .push_bind(self.resource_id.clone());
+        }
+        for (column, range) in [("created_at", &self.created), ("updated_at", &self.updated)] {
+            if let Some(range) = range.as_ref() {
+                range.push_conditions(builder, column);
+            }
+        }
+        self.push_window_condition(builder);
+        if !self.note.is_empty() {
+            // match the note literally, % and _ are not wildcards
+            let note = self
+                .note
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            builder
+                .push(" AND note ILIKE ")
+                .push_bind(format!("%{}%", note));
+        }
+    }
+
+    /// time window of the filter, a bound not set is unbounded
+    fn get_window(&self) -> TimeRange {
+        TimeRange {
+            start: self.start.clone(),
+            end: self.end.clone(),
+        }
+    }
+
+    /// push the condition of the timespan against the time window
+    fn push_window_condition(&self, builder: &mut QueryBuilder<'static, Postgres>) {
+        if self.start.is_none() && self.end.is_none() {
+            return;
+        }
+        let window = self.get_window().get_range();
+        match ReservationMatchMode::from_i32(self.match_mode).unwrap_or_default() {
+            ReservationMatchMode::Contained => builder.push(" AND timespan <@ ").push_bind(window),
+            ReservationMatchMode::Overlapping => {
+                builder.push(" AND timespan && ").push_bind(window)
+            }
+            ReservationMatchMode::StartingWithin => {
+                builder.push(" AND lower(timespan) <@ ").push_bind(window)
+            }
+            // the end of a reservation is exclusive, so it ends within (start, end]
+            ReservationMatchMode::EndingWithin => {
+                let window = PgRange {
+                    start: match window.start {
+                        Bound::Included(start) => Bound::Excluded(start),
+                        bound => bound,
+                    },
+                    end: match window.end {
+                        Bound::Excluded(end) => Bound::Included(end),
+                        bound => bound,
+                    },
+                };
+                builder.push(" AND upper(timespan) <@ ").push_bind(window)
+            }
+        };
+    }
+
+    /// sql counting all reservations matched by the filter, regardless of the page
+    pub fn to_count_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new("SELECT count(*) FROM rsvp.reservations WHERE ");
+        self.push_match_conditions(&mut builder);
+        builder
+    }
+
+    /// sql locking all reservations matched by the filter for a change, regardless of the page
+    pub fn to_lock_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new("SELECT * FROM rsvp.reservations WHERE ");
+        self.push_match_conditions(&mut builder);
+        builder.push(" ORDER BY id FOR UPDATE");
+        builder
+    }
+
+    /// sql of the query plan of all reservations matched by the filter, the first line holds
+    /// the estimated number of rows
+    pub fn to_estimate_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new("EXPLAIN SELECT 1 FROM rsvp.reservations WHERE ");
+        self.push_match_conditions(&mut builder);
+        builder
+    }
 }
 
 impl ToSql for ReservationFilter {
-    fn to_sql(&self) -> Result<String, Error> {
-        let middle_plus = if self.cursor.is_none() { 0 } else { 1 };
-        let mut sql = format!(
-            "SELECT * FROM rsvp.reservations WHERE status = '{}'::rsvp.reservation_status AND ",
-            self.get_status()
-        );
-        if self.desc {
-            sql.push_str(&format!("id <= {} AND ", self.get_cursor()));
-        } else {
-            sql.push_str(&format!("id >= {} AND ", self.get_cursor()));
-        }
+    fn to_sql(&self) -> Result<QueryBuilder<'static, Postgres>, Error> {
+        let cursor = self.get_page_cursor()?;
+        let middle_plus = match (&cursor, self.cursor) {
+            (None, Some(_)) => 1,
+            _ => 0,
+        };
+        let mut builder = QueryBuilder::new("SELECT * FROM rsvp.reservations WHERE ");
+        self.push_cursor_condition(&mut builder);
+        builder.push(" AND ");
+        self.push_match_conditions(&mut builder);
 
-        if self.user_id.is_empty() && self.resource_id.is_empty() {
-            sql.push_str("TRUE ");
-        } else if self.user_id.is_empty() {
-            sql.push_str(&format!("resource_id = '{}' ", self.resource_id));
-        } else if self.resource_id.is_empty() {
-            sql.push_str(&format!("user_id = '{}' ", self.user_id));
+        let backward = cursor.is_some_and(|c| c.backward);
+        let direction = if self.desc != backward { "DESC" } else { "ASC" };
+        let column = self.sort_column();
+        if column == "id" {
+            builder.push(format!(" ORDER BY id {}", direction));
         } else {
-            sql.push_str(&format!(
-                "user_id = '{}' AND resource_id = '{}' ",
-                self.user_id, self.resource_id
+            builder.push(format!(
+                " ORDER BY {} {}, id {}",
+                column, direction, direction
             ));
         }
+        builder
+            .push(" LIMIT ")
+            .push_bind(self.page_size + 1 + middle_plus);
 
-        sql.push_str(&format!(
-            "ORDER BY id {} LIMIT {}",
-            if self.desc { "DESC" } else { "ASC" },
-            self.page_size + 1 + middle_plus
-        ));
-
-        Ok(sql)
+        Ok(builder)
     }
 }
 
@@ -113,8 +395,7 @@ mod tests {
             .build()
             .unwrap();
         let sql = filter.to_sql().unwrap();
-
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'tyr' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id >= $1 AND status <> 'cancelled'::rsvp.reservation_status AND user_id = $2 ORDER BY id ASC LIMIT $3");
 
         let filter = ReservationFilterBuilder::default()
             .user_id("tyr")
@@ -122,33 +403,208 @@ mod tests {
             .build()
             .unwrap();
         let sql = filter.to_sql().unwrap();
-
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'tyr' AND resource_id = 'test' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id >= $1 AND status <> 'cancelled'::rsvp.reservation_status AND user_id = $2 AND resource_id = $3 ORDER BY id ASC LIMIT $4");
 
         let filter = ReservationFilterBuilder::default()
             .desc(true)
             .build()
             .unwrap();
         let sql = filter.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id <= $1 AND status <> 'cancelled'::rsvp.reservation_status ORDER BY id DESC LIMIT $2");
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("tyr")
+            .include_cancelled(true)
+            .build()
+            .unwrap();
+        let sql = filter.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id >= $1 AND TRUE AND user_id = $2 ORDER BY id ASC LIMIT $3");
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("tyr")
+            .status(ReservationStatus::Pending as i32)
+            .statuses(vec![ReservationStatus::Confirmed as i32])
+            .include_cancelled(true)
+            .cursor(100)
+            .build()
+            .unwrap();
+        let sql = filter.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id >= $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = $3 ORDER BY id ASC LIMIT $4");
+
+        let filter = ReservationFilterBuilder::default()
+            .statuses(vec![ReservationStatus::Pending as i32, 10])
+            .build();
+        assert_eq!(filter, Err(Error::InvalidStatus(10)));
+    }
 
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 9223372036854775807 AND TRUE ORDER BY id DESC LIMIT 11");
+    #[test]
+    fn filter_should_bind_hostile_ids() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("tyr' OR '1'='1")
+            .resource_id("room'); DROP TABLE rsvp.reservations; --")
+            .build()
+            .unwrap();
+        for sql in [
+            filter.to_sql().unwrap().into_sql(),
+            filter.to_count_sql().into_sql(),
+            filter.to_estimate_sql().into_sql(),
+        ] {
+            assert!(!sql.contains("tyr"));
+            assert!(!sql.contains("DROP"));
+            assert!(sql.contains("user_id = $") && sql.contains("resource_id = $"));
+        }
+    }
 
+    #[test]
+    fn filter_should_generate_count_sql() {
         let filter = ReservationFilterBuilder::default()
             .user_id("tyr")
+            .status(ReservationStatus::Pending as i32)
             .cursor(100)
+            .updated(TimeRange {
+                start: None,
+                end: "2022-12-27T15:00:00-0700".parse().ok(),
+            })
+            .build()
+            .unwrap();
+        assert_eq!(filter.to_count_sql().sql(), "SELECT count(*) FROM rsvp.reservations WHERE status = ANY($1::rsvp.reservation_status[]) AND user_id = $2 AND updated_at < $3");
+        assert_eq!(filter.to_estimate_sql().sql(), "EXPLAIN SELECT 1 FROM rsvp.reservations WHERE status = ANY($1::rsvp.reservation_status[]) AND user_id = $2 AND updated_at < $3");
+        assert_eq!(filter.to_lock_sql().sql(), "SELECT * FROM rsvp.reservations WHERE status = ANY($1::rsvp.reservation_status[]) AND user_id = $2 AND updated_at < $3 ORDER BY id FOR UPDATE");
+    }
+
+    #[test]
+    fn filter_should_page_by_opaque_cursor() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("tyr")
+            .sort_by(ReservationSortBy::Start as i32)
+            .build()
+            .unwrap();
+        let mut data: VecDeque<Reservation> = (1..=11)
+            .map(|id| {
+                let mut rsvp = Reservation::new_pending(
+                    "tyr",
+                    "room",
+                    "2022-12-26T15:00:00-0700".parse().unwrap(),
+                    "2022-12-27T15:00:00-0700".parse().unwrap(),
+                    "",
+                );
+                rsvp.id = id;
+                rsvp
+            })
+            .collect();
+        let pager = filter.get_pager(&mut data, None).unwrap();
+        assert_eq!(data.len(), 10);
+        assert_eq!(pager.prev_cursor, "");
+        assert_ne!(pager.next_cursor, "");
+        let next_cursor = pager.next_cursor;
+
+        let next = ReservationFilterBuilder::default()
+            .user_id("tyr")
+            .sort_by(ReservationSortBy::Start as i32)
+            .page_cursor(next_cursor.clone())
+            .build()
+            .unwrap();
+        let sql = next.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE (lower(timespan), id) > ($1, $2) AND status <> 'cancelled'::rsvp.reservation_status AND user_id = $3 ORDER BY lower(timespan) ASC, id ASC LIMIT $4");
+        let pager = next.get_pager(&mut data, None).unwrap();
+        assert_ne!(pager.prev_cursor, "");
+        assert_eq!(pager.next_cursor, "");
+
+        // the prev page is read backward
+        let prev = ReservationFilterBuilder::default()
+            .user_id("tyr")
+            .sort_by(ReservationSortBy::Start as i32)
+            .page_cursor(pager.prev_cursor)
+            .build()
+            .unwrap();
+        let sql = prev.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE (lower(timespan), id) < ($1, $2) AND status <> 'cancelled'::rsvp.reservation_status AND user_id = $3 ORDER BY lower(timespan) DESC, id DESC LIMIT $4");
+
+        // the cursor only works for the sort key and direction it was made for
+        for filter in [
+            ReservationFilterBuilder::default()
+                .page_cursor(next_cursor.clone())
+                .build(),
+            ReservationFilterBuilder::default()
+                .sort_by(ReservationSortBy::Start as i32)
+                .desc(true)
+                .page_cursor(next_cursor.clone())
+                .build(),
+            ReservationFilterBuilder::default()
+                .page_cursor("not a cursor")
+                .build(),
+            // the start time is out of range
+            ReservationFilterBuilder::default()
+                .sort_by(ReservationSortBy::Start as i32)
+                .page_cursor(encode_token(&format!(
+                    "{}:0:0:{}:0:1",
+                    ReservationSortBy::Start as i32,
+                    i64::MAX
+                )))
+                .build(),
+            // a cursor without the token version
+            ReservationFilterBuilder::default()
+                .sort_by(ReservationSortBy::Start as i32)
+                .page_cursor(&next_cursor[2..])
+                .build(),
+        ] {
+            assert!(matches!(filter, Err(Error::InvalidPageToken(_))));
+        }
+    }
+
+    #[test]
+    fn filter_should_match_window_and_note() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("room")
+            .status(ReservationStatus::Confirmed as i32)
+            .start("2022-12-26T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-01-02T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .note("  demo ")
             .build()
             .unwrap();
+        assert_eq!(filter.note, "demo");
         let sql = filter.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE id >= $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = $3 AND timespan <@ $4 AND note ILIKE $5 ORDER BY id ASC LIMIT $6");
+
+        let filter = ReservationFilterBuilder::default()
+            .end("2023-01-02T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .match_mode(ReservationMatchMode::EndingWithin as i32)
+            .build()
+            .unwrap();
+        assert_eq!(filter.to_count_sql().sql(), "SELECT count(*) FROM rsvp.reservations WHERE status <> 'cancelled'::rsvp.reservation_status AND upper(timespan) <@ $1");
 
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 100 AND user_id = 'tyr' ORDER BY id ASC LIMIT 12");
+        let filter = ReservationFilterBuilder::default()
+            .start("2023-01-02T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2022-12-26T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .build();
+        assert_eq!(filter, Err(Error::InvalidTime));
+    }
 
+    #[test]
+    fn filter_should_sort_and_filter_by_timestamps() {
         let filter = ReservationFilterBuilder::default()
             .user_id("tyr")
-            .cursor(10)
+            .sort_by(ReservationSortBy::CreatedAt as i32)
+            .created(TimeRange {
+                start: "2022-12-26T15:00:00-0700".parse().ok(),
+                end: "2022-12-27T15:00:00-0700".parse().ok(),
+            })
+            .build()
+            .unwrap();
+        let sql = filter.to_sql().unwrap();
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE TRUE AND status <> 'cancelled'::rsvp.reservation_status AND user_id = $1 AND created_at >= $2 AND created_at < $3 ORDER BY created_at ASC, id ASC LIMIT $4");
+
+        let filter = ReservationFilterBuilder::default()
+            .sort_by(ReservationSortBy::UpdatedAt as i32)
+            .updated(TimeRange {
+                start: None,
+                end: "2022-12-27T15:00:00-0700".parse().ok(),
+            })
+            .cursor(100)
             .desc(true)
             .build()
             .unwrap();
         let sql = filter.to_sql().unwrap();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 10 AND user_id = 'tyr' ORDER BY id DESC LIMIT 12");
+        assert_eq!(sql.sql(), "SELECT * FROM rsvp.reservations WHERE (updated_at, id) <= (SELECT updated_at, id FROM rsvp.reservations WHERE id = $1) AND status <> 'cancelled'::rsvp.reservation_status AND updated_at < $2 ORDER BY updated_at DESC, id DESC LIMIT $3");
     }
 }
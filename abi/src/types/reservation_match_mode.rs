@@ -0,0 +1,13 @@
+use crate::ReservationMatchMode;
+use std::fmt;
+
+impl fmt::Display for ReservationMatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationMatchMode::Contained => write!(f, "contained"),
+            ReservationMatchMode::Overlapping => write!(f, "overlapping"),
+            ReservationMatchMode::StartingWithin => write!(f, "starting_within"),
+            ReservationMatchMode::EndingWithin => write!(f, "ending_within"),
+        }
+    }
+}
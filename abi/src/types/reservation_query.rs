@@ -1,13 +1,70 @@
-use crate::{Error, ReservationQuery, Validator};
-use chrono::{DateTime, Utc};
+use crate::{
+    Error, Reservation, ReservationQuery, ReservationSortBy, ReservationStatus, Validator,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use sqlx::postgres::types::PgRange;
 
-use super::{get_timespan, validate_range};
+use super::{
+    decode_token, encode_token, get_timespan,
+    reservation_status::{merge_statuses, validate_statuses},
+    validate_range,
+};
+
+/// max number of reservations in a page of query results
+const MAX_PAGE_SIZE: i64 = 100;
 
 impl ReservationQuery {
     pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    /// all the statuses to match, empty means any status
+    pub fn get_statuses(&self) -> Result<Vec<ReservationStatus>, Error> {
+        validate_statuses(self.status, &self.statuses)?;
+        Ok(merge_statuses(self.status, &self.statuses))
+    }
+
+    /// the page size, if the query is paged. Paged queries are ordered by start time and id
+    pub fn get_page_size(&self) -> Result<Option<i64>, Error> {
+        let size = match self.page_size {
+            0 => return Ok(None),
+            size if size > 0 && size <= MAX_PAGE_SIZE => size,
+            size => return Err(Error::InvalidPageSize(size)),
+        };
+        match ReservationSortBy::from_i32(self.sort_by) {
+            Some(ReservationSortBy::Default | ReservationSortBy::Start) => Ok(Some(size)),
+            _ => Err(Error::InvalidSortBy(self.sort_by)),
+        }
+    }
+
+    /// start time and id of the last reservation of the previous page
+    pub fn get_page_cursor(&self) -> Result<Option<(DateTime<Utc>, i64)>, Error> {
+        if self.page_token.is_empty() {
+            return Ok(None);
+        }
+        let invalid = || Error::InvalidPageToken(self.page_token.clone());
+        let token = decode_token(&self.page_token).ok_or_else(invalid)?;
+        let mut parts = token.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(seconds)), Some(Some(nanos)), Some(Some(id)))
+                if (0..1_000_000_000).contains(&nanos) && id > 0 =>
+            {
+                // the token could be forged, so the time may be out of range
+                let start = Utc
+                    .timestamp_opt(seconds, nanos as u32)
+                    .single()
+                    .ok_or_else(invalid)?;
+                Ok(Some((start, id)))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// continuation token of the page after the given reservation
+    pub fn next_page_token(last: &Reservation) -> String {
+        let start = last.start.clone().unwrap_or_default();
+        encode_token(&format!("{}:{}:{}", start.seconds, start.nanos, last.id))
+    }
 }
 
 impl Validator for ReservationQuery {
@@ -16,3 +73,73 @@ impl Validator for ReservationQuery {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convert_to_utc_time, ReservationQueryBuilder};
+
+    #[test]
+    fn page_token_should_round_trip() {
+        let mut rsvp = Reservation::new_pending(
+            "tyr",
+            "room-1",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        rsvp.id = 42;
+        let token = ReservationQuery::next_page_token(&rsvp);
+        let query = ReservationQueryBuilder::default()
+            .page_size(10)
+            .page_token(token)
+            .build()
+            .unwrap();
+        assert_eq!(query.get_page_size(), Ok(Some(10)));
+        assert_eq!(
+            query.get_page_cursor(),
+            Ok(Some((convert_to_utc_time(rsvp.start.unwrap()), 42)))
+        );
+    }
+
+    #[test]
+    fn invalid_page_should_be_rejected() {
+        let query = ReservationQueryBuilder::default()
+            .page_size(101)
+            .build()
+            .unwrap();
+        assert_eq!(query.get_page_size(), Err(Error::InvalidPageSize(101)));
+
+        let query = ReservationQueryBuilder::default()
+            .page_size(10)
+            .sort_by(ReservationSortBy::Id as i32)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.get_page_size(),
+            Err(Error::InvalidSortBy(ReservationSortBy::Id as i32))
+        );
+
+        // the start time of the last token is out of range
+        let forged = encode_token(&format!("{}:0:1", i64::MAX));
+        // "1:2:3" without the token version
+        let unversioned = "313a323a33";
+        for token in [
+            "xyz",
+            "abc",
+            "313a323a",
+            "313a323a2d33",
+            unversioned,
+            &forged,
+        ] {
+            let query = ReservationQueryBuilder::default()
+                .page_token(token)
+                .build()
+                .unwrap();
+            assert_eq!(
+                query.get_page_cursor(),
+                Err(Error::InvalidPageToken(token.into()))
+            );
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::ReservationSortBy;
+use std::fmt;
+
+impl fmt::Display for ReservationSortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationSortBy::Default => write!(f, "default"),
+            ReservationSortBy::Id => write!(f, "id"),
+            ReservationSortBy::Start => write!(f, "start"),
+            ReservationSortBy::CreatedAt => write!(f, "created_at"),
+            ReservationSortBy::UpdatedAt => write!(f, "updated_at"),
+            ReservationSortBy::End => write!(f, "end"),
+        }
+    }
+}
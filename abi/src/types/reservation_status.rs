@@ -1,6 +1,95 @@
-use crate::{ReservationStatus, RsvpStatus};
+use crate::{Error, ReservationStatus, RsvpStatus};
+use sqlx::{Postgres, QueryBuilder};
 use std::fmt;
 
+impl ReservationStatus {
+    /// check if a reservation could move from the current status to the given one.
+    /// Staying in the same status is always allowed, so the transitions are idempotent.
+    ///
+    /// pending -> confirmed | blocked
+    /// confirmed -> blocked
+    /// blocked -> pending
+    /// pending | confirmed | blocked -> cancelled
+    pub fn can_transition_to(&self, to: ReservationStatus) -> bool {
+        use ReservationStatus::*;
+        matches!(
+            (*self, to),
+            (Pending, Pending)
+                | (Confirmed, Confirmed)
+                | (Blocked, Blocked)
+                | (Cancelled, Cancelled)
+                | (Pending, Confirmed)
+                | (Pending, Blocked)
+                | (Confirmed, Blocked)
+                | (Blocked, Pending)
+                | (Pending | Confirmed | Blocked, Cancelled)
+        )
+    }
+
+    /// return the target status if the transition is allowed
+    pub fn transition(&self, to: ReservationStatus) -> Result<ReservationStatus, Error> {
+        if to == ReservationStatus::Unknown {
+            return Err(Error::InvalidStatus(to as i32));
+        }
+        if !self.can_transition_to(to) {
+            return Err(Error::InvalidTransition(*self, to));
+        }
+        Ok(to)
+    }
+}
+
+/// merge the single status and the status list of a query or filter into a sorted, deduplicated
+/// set. UNKNOWN means no restriction, so it is dropped; an empty set matches any status
+pub(crate) fn merge_statuses(status: i32, statuses: &[i32]) -> Vec<ReservationStatus> {
+    let mut merged: Vec<ReservationStatus> = std::iter::once(&status)
+        .chain(statuses)
+        .filter_map(|s| ReservationStatus::from_i32(*s))
+        .filter(|s| *s != ReservationStatus::Unknown)
+        .collect();
+    merged.sort();
+    merged.dedup();
+    merged
+}
+
+/// validate every status of a query or filter
+pub(crate) fn validate_statuses(status: i32, statuses: &[i32]) -> Result<(), Error> {
+    match std::iter::once(&status)
+        .chain(statuses)
+        .find(|s| ReservationStatus::from_i32(**s).is_none())
+    {
+        Some(s) => Err(Error::InvalidStatus(*s)),
+        None => Ok(()),
+    }
+}
+
+/// push the condition matching the given statuses. Cancelled reservations are matched only if
+/// they are listed explicitly or include_cancelled is set
+pub(crate) fn push_status_condition(
+    builder: &mut QueryBuilder<'static, Postgres>,
+    statuses: &[ReservationStatus],
+    include_cancelled: bool,
+) {
+    let mut statuses: Vec<String> = statuses.iter().map(|s| s.to_string()).collect();
+    match statuses.is_empty() {
+        true if include_cancelled => {
+            builder.push("TRUE");
+        }
+        true => {
+            builder.push("status <> 'cancelled'::rsvp.reservation_status");
+        }
+        false => {
+            let cancelled = ReservationStatus::Cancelled.to_string();
+            if include_cancelled && !statuses.contains(&cancelled) {
+                statuses.push(cancelled);
+            }
+            builder
+                .push("status = ANY(")
+                .push_bind(statuses)
+                .push("::rsvp.reservation_status[])");
+        }
+    }
+}
+
 impl From<RsvpStatus> for ReservationStatus {
     fn from(status: RsvpStatus) -> Self {
         match status {
@@ -8,6 +97,7 @@ impl From<RsvpStatus> for ReservationStatus {
             RsvpStatus::Pending => ReservationStatus::Pending,
             RsvpStatus::Confirmed => ReservationStatus::Confirmed,
             RsvpStatus::Blocked => ReservationStatus::Blocked,
+            RsvpStatus::Cancelled => ReservationStatus::Cancelled,
         }
     }
 }
@@ -18,7 +108,74 @@ impl fmt::Display for ReservationStatus {
             ReservationStatus::Pending => write!(f, "pending"),
             ReservationStatus::Blocked => write!(f, "blocked"),
             ReservationStatus::Confirmed => write!(f, "confirmed"),
+            ReservationStatus::Cancelled => write!(f, "cancelled"),
             ReservationStatus::Unknown => write!(f, "unknown"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ReservationStatus::*;
+
+    #[test]
+    fn statuses_should_merge_into_condition() {
+        let condition = |statuses: &[ReservationStatus], include_cancelled| {
+            let mut builder = QueryBuilder::new("");
+            push_status_condition(&mut builder, statuses, include_cancelled);
+            builder.into_sql()
+        };
+        let statuses = merge_statuses(Unknown as i32, &[]);
+        assert!(statuses.is_empty());
+        assert_eq!(
+            condition(&statuses, false),
+            "status <> 'cancelled'::rsvp.reservation_status"
+        );
+        assert_eq!(condition(&statuses, true), "TRUE");
+
+        let statuses = merge_statuses(Pending as i32, &[Pending as i32]);
+        assert_eq!(statuses, vec![Pending]);
+        assert_eq!(
+            condition(&statuses, false),
+            "status = ANY($1::rsvp.reservation_status[])"
+        );
+
+        let statuses = merge_statuses(Confirmed as i32, &[Pending as i32]);
+        assert_eq!(statuses, vec![Pending, Confirmed]);
+
+        assert_eq!(
+            validate_statuses(Pending as i32, &[Blocked as i32, 10]),
+            Err(Error::InvalidStatus(10))
+        );
+    }
+
+    #[test]
+    fn status_transition_should_follow_state_machine() {
+        assert_eq!(Pending.transition(Confirmed), Ok(Confirmed));
+        assert_eq!(Pending.transition(Blocked), Ok(Blocked));
+        assert_eq!(Confirmed.transition(Blocked), Ok(Blocked));
+        assert_eq!(Blocked.transition(Pending), Ok(Pending));
+        assert_eq!(Confirmed.transition(Confirmed), Ok(Confirmed));
+        assert_eq!(Blocked.transition(Cancelled), Ok(Cancelled));
+        assert_eq!(Cancelled.transition(Cancelled), Ok(Cancelled));
+
+        assert_eq!(
+            Confirmed.transition(Pending),
+            Err(Error::InvalidTransition(Confirmed, Pending))
+        );
+        assert_eq!(
+            Blocked.transition(Confirmed),
+            Err(Error::InvalidTransition(Blocked, Confirmed))
+        );
+        assert_eq!(
+            Cancelled.transition(Pending),
+            Err(Error::InvalidTransition(Cancelled, Pending))
+        );
+        assert_eq!(
+            Unknown.transition(Pending),
+            Err(Error::InvalidTransition(Unknown, Pending))
+        );
+        assert_eq!(Pending.transition(Unknown), Err(Error::InvalidStatus(0)));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{Error, Reservation, ReserveRequest, Validator};
+
+/// max number of free windows to suggest for a conflicting reservation
+const MAX_SUGGESTIONS: u32 = 10;
+
+impl ReserveRequest {
+    pub fn new(reservation: Reservation) -> Self {
+        Self {
+            reservation: Some(reservation),
+            suggestions: 0,
+        }
+    }
+
+    /// suggest up to n free windows if the reservation conflicts
+    pub fn with_suggestions(reservation: Reservation, n: u32) -> Self {
+        Self {
+            reservation: Some(reservation),
+            suggestions: n,
+        }
+    }
+}
+
+impl Validator for ReserveRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.suggestions > MAX_SUGGESTIONS {
+            return Err(Error::InvalidSuggestionCount(self.suggestions));
+        }
+        // the reservation itself is validated when it is made
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_request_should_be_validated() {
+        let rsvp = Reservation::new_pending(
+            "tyr",
+            "ixia-3230",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let mut req = ReserveRequest::with_suggestions(rsvp, MAX_SUGGESTIONS);
+        assert!(req.validate().is_ok());
+
+        req.suggestions = MAX_SUGGESTIONS + 1;
+        assert_eq!(
+            req.validate(),
+            Err(Error::InvalidSuggestionCount(MAX_SUGGESTIONS + 1))
+        );
+    }
+}
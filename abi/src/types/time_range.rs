@@ -0,0 +1,68 @@
+use std::ops::Bound;
+
+use crate::{convert_to_utc_time, Error, TimeRange, Validator};
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
+
+impl TimeRange {
+    /// start is inclusive and end is exclusive, a bound not set is unbounded
+    pub fn get_range(&self) -> PgRange<DateTime<Utc>> {
+        let f = |ts: &Option<prost_types::Timestamp>| ts.clone().map(convert_to_utc_time);
+        PgRange {
+            start: f(&self.start).map_or(Bound::Unbounded, Bound::Included),
+            end: f(&self.end).map_or(Bound::Unbounded, Bound::Excluded),
+        }
+    }
+
+    /// push the conditions for the column to be in the range, each prefixed with AND
+    pub(crate) fn push_conditions(
+        &self,
+        builder: &mut QueryBuilder<'static, Postgres>,
+        column: &str,
+    ) {
+        let range = self.get_range();
+        if let Bound::Included(start) = range.start {
+            builder
+                .push(format!(" AND {} >= ", column))
+                .push_bind(start);
+        }
+        if let Bound::Excluded(end) = range.end {
+            builder.push(format!(" AND {} < ", column)).push_bind(end);
+        }
+    }
+}
+
+impl Validator for TimeRange {
+    fn validate(&self) -> Result<(), Error> {
+        if let (Some(start), Some(end)) = (self.start.as_ref(), self.end.as_ref()) {
+            if (start.seconds, start.nanos) >= (end.seconds, end.nanos) {
+                return Err(Error::InvalidTime);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_range_should_allow_open_bounds() {
+        let range = TimeRange {
+            start: "2022-12-26T15:00:00-0700".parse().ok(),
+            end: None,
+        };
+        assert!(range.validate().is_ok());
+        let mut builder = QueryBuilder::new("TRUE");
+        range.push_conditions(&mut builder, "created_at");
+        assert_eq!(builder.sql(), "TRUE AND created_at >= $1");
+        assert_eq!(range.get_range().end, Bound::Unbounded);
+
+        let range = TimeRange {
+            start: "2022-12-26T15:00:00-0700".parse().ok(),
+            end: "2022-12-26T14:00:00-0700".parse().ok(),
+        };
+        assert_eq!(range.validate(), Err(Error::InvalidTime));
+    }
+}
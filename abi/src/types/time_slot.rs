@@ -0,0 +1,17 @@
+use crate::{convert_to_timestamp, TimeSlot};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::types::PgRange;
+
+use super::NaiveRange;
+
+impl TimeSlot {
+    /// create a time slot from a timespan, unbounded start or end will be None
+    pub fn new(rid: impl Into<String>, timespan: PgRange<DateTime<Utc>>) -> Self {
+        let range: NaiveRange<DateTime<Utc>> = timespan.into();
+        Self {
+            resource_id: rid.into(),
+            start: range.start.map(convert_to_timestamp),
+            end: range.end.map(convert_to_timestamp),
+        }
+    }
+}
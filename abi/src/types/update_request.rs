@@ -0,0 +1,171 @@
+use crate::{Error, Reservation, ReservationStatus, UpdateRequest, Validator};
+use prost_types::FieldMask;
+
+/// fields of a reservation that could be updated by an UpdateRequest
+const UPDATABLE_FIELDS: [&str; 6] = ["note", "start", "end", "resource_id", "user_id", "status"];
+
+impl UpdateRequest {
+    pub fn new(id: i64, reservation: Reservation, paths: &[&str]) -> Self {
+        Self {
+            id,
+            reservation: Some(reservation),
+            update_mask: Some(FieldMask {
+                paths: paths.iter().map(|p| p.to_string()).collect(),
+            }),
+            expected_version: None,
+        }
+    }
+
+    /// copy the masked fields from the request to the given reservation,
+    /// and validate the result
+    pub fn apply(&self, mut rsvp: Reservation) -> Result<Reservation, Error> {
+        self.validate()?;
+        let new = self.reservation.as_ref().unwrap();
+        for path in self.paths() {
+            match path.as_str() {
+                "note" => rsvp.note = new.note.clone(),
+                "start" => rsvp.start = new.start.clone(),
+                "end" => rsvp.end = new.end.clone(),
+                "resource_id" => rsvp.resource_id = new.resource_id.clone(),
+                "user_id" => rsvp.user_id = new.user_id.clone(),
+                "status" => rsvp.status = rsvp.status().transition(new.status())? as i32,
+                _ => unreachable!("update mask is validated"),
+            }
+        }
+        rsvp.validate()?;
+        Ok(rsvp)
+    }
+
+    fn paths(&self) -> &[String] {
+        self.update_mask
+            .as_ref()
+            .map(|mask| mask.paths.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl Validator for UpdateRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.id.validate()?;
+        let paths = self.paths();
+        if paths.is_empty() {
+            return Err(Error::InvalidUpdateMask("empty update mask".into()));
+        }
+        if let Some(path) = paths
+            .iter()
+            .find(|p| !UPDATABLE_FIELDS.contains(&p.as_str()))
+        {
+            return Err(Error::InvalidUpdateMask(path.clone()));
+        }
+        let rsvp = self
+            .reservation
+            .as_ref()
+            .ok_or_else(|| Error::InvalidUpdateMask("missing reservation".into()))?;
+        if paths.iter().any(|p| p == "status") {
+            // cancel goes through cancel, so the reason is recorded
+            match ReservationStatus::from_i32(rsvp.status) {
+                None | Some(ReservationStatus::Unknown) | Some(ReservationStatus::Cancelled) => {
+                    return Err(Error::InvalidStatus(rsvp.status))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice_reservation() -> Reservation {
+        Reservation::new_pending(
+            "aliceid",
+            "ixia-3230",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello",
+        )
+    }
+
+    #[test]
+    fn update_request_should_only_apply_masked_fields() {
+        let mut new = Reservation::new_pending(
+            "bobid",
+            "ixia-3231",
+            "2022-12-27T15:00:00-0700".parse().unwrap(),
+            "2022-12-31T12:00:00-0700".parse().unwrap(),
+            "world",
+        );
+        new.status = ReservationStatus::Confirmed as i32;
+        let old = alice_reservation();
+
+        let req = UpdateRequest::new(1, new.clone(), &["note", "status"]);
+        let rsvp = req.apply(old.clone()).unwrap();
+        assert_eq!(rsvp.note, "world");
+        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+        assert_eq!(rsvp.user_id, old.user_id);
+        assert_eq!(rsvp.resource_id, old.resource_id);
+        assert_eq!(rsvp.start, old.start);
+
+        let req = UpdateRequest::new(1, new.clone(), &["start", "end", "resource_id", "user_id"]);
+        let rsvp = req.apply(old).unwrap();
+        assert_eq!(rsvp.start, new.start);
+        assert_eq!(rsvp.end, new.end);
+        assert_eq!(rsvp.resource_id, new.resource_id);
+        assert_eq!(rsvp.user_id, new.user_id);
+        assert_eq!(rsvp.note, "hello");
+    }
+
+    #[test]
+    fn update_request_should_be_validated() {
+        let new = alice_reservation();
+        let req = UpdateRequest::new(1, new.clone(), &[]);
+        assert_eq!(
+            req.validate(),
+            Err(Error::InvalidUpdateMask("empty update mask".into()))
+        );
+
+        let req = UpdateRequest::new(1, new.clone(), &["id"]);
+        assert_eq!(req.validate(), Err(Error::InvalidUpdateMask("id".into())));
+
+        let req = UpdateRequest::new(0, new.clone(), &["note"]);
+        assert_eq!(req.validate(), Err(Error::InvalidReservationId(0)));
+
+        let mut unknown = new.clone();
+        unknown.status = ReservationStatus::Unknown as i32;
+        let req = UpdateRequest::new(1, unknown, &["status"]);
+        assert_eq!(req.validate(), Err(Error::InvalidStatus(0)));
+
+        let mut cancelled = new.clone();
+        cancelled.status = ReservationStatus::Cancelled as i32;
+        let req = UpdateRequest::new(1, cancelled, &["status"]);
+        assert_eq!(
+            req.validate(),
+            Err(Error::InvalidStatus(ReservationStatus::Cancelled as i32))
+        );
+
+        // the updated reservation must still be valid
+        let mut empty = new.clone();
+        empty.user_id = "".into();
+        let req = UpdateRequest::new(1, empty, &["user_id"]);
+        assert_eq!(req.apply(new.clone()), Err(Error::InvalidUserId("".into())));
+
+        let mut late = new.clone();
+        late.start = "2023-01-01T15:00:00-0700".parse().ok();
+        let req = UpdateRequest::new(1, late, &["start"]);
+        assert_eq!(req.apply(new.clone()), Err(Error::InvalidTime));
+
+        // status follows the state machine
+        let mut confirmed = new.clone();
+        confirmed.status = ReservationStatus::Confirmed as i32;
+        let req = UpdateRequest::new(1, new.clone(), &["status"]);
+        assert_eq!(
+            req.apply(confirmed),
+            Err(Error::InvalidTransition(
+                ReservationStatus::Confirmed,
+                ReservationStatus::Pending
+            ))
+        );
+    }
+}
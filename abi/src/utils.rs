@@ -1,11 +1,9 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
-use prost_types::Timestamp;
+use chrono::{DateTime, TimeZone, Utc};
+use prost_types::{Duration, Timestamp};
+use sqlx::postgres::types::PgInterval;
 
 pub fn convert_to_utc_time(ts: Timestamp) -> DateTime<Utc> {
-    DateTime::<Utc>::from_utc(
-        NaiveDateTime::from_timestamp_opt(ts.seconds, ts.nanos as _).unwrap(),
-        Utc,
-    )
+    Utc.timestamp_opt(ts.seconds, ts.nanos as _).unwrap()
 }
 
 pub fn convert_to_timestamp(dt: DateTime<Utc>) -> Timestamp {
@@ -14,3 +12,16 @@ pub fn convert_to_timestamp(dt: DateTime<Utc>) -> Timestamp {
         nanos: dt.timestamp_subsec_nanos() as _,
     }
 }
+
+pub fn convert_to_interval(d: Duration) -> PgInterval {
+    // postgres interval only supports microseconds precision
+    let microseconds = d
+        .seconds
+        .saturating_mul(1_000_000)
+        .saturating_add(d.nanos as i64 / 1000);
+    PgInterval {
+        months: 0,
+        days: 0,
+        microseconds,
+    }
+}
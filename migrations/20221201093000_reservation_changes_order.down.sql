@@ -0,0 +1,30 @@
+DROP TRIGGER reservations_trigger ON rsvp.reservations;
+
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS
+$$
+  BEGIN
+    IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes(reservation_id, op) VALUES (NEW.id, 'create');
+    ELSIF TG_OP = 'UPDATE' THEN
+    -- if stataus changed, update reservation_changes
+      IF OLD.status <> NEW.status THEN
+        INSERT INTO rsvp.reservation_changes(reservation_id, op) VALUES (NEW.id, 'update');
+      END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+      -- update reservation_changes
+      INSERT INTO rsvp.reservation_changes(reservation_id, op) VALUES (OLD.id, 'delete');
+    END IF;
+    -- notify a channel called reservation_update
+    NOTIFY reservation_update;
+    RETURN NULL;
+  END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER reservations_trigger
+    AFTER INSERT OR UPDATE OR DELETE ON rsvp.reservations
+    FOR EACH ROW EXECUTE PROCEDURE rsvp.reservations_trigger();
+
+ALTER TABLE rsvp.reservation_changes
+  DROP COLUMN old,
+  DROP COLUMN new;
@@ -0,0 +1,39 @@
+-- record reservation changes when the transaction commits, and serialize the commits,
+-- so the change ids are in commit order. Listeners could resume from the last change id
+-- they've seen without missing any changes.
+-- keep the row before and after the change as well, so a replayed change has the reservation
+-- as the change left it, even if it was changed again or deleted since.
+ALTER TABLE rsvp.reservation_changes
+  ADD COLUMN old jsonb,
+  ADD COLUMN new jsonb;
+
+DROP TRIGGER reservations_trigger ON rsvp.reservations;
+
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS
+$$
+  BEGIN
+    -- released when the transaction ends
+    PERFORM pg_advisory_xact_lock(hashtext('rsvp.reservation_changes'));
+    IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes(reservation_id, op, new) VALUES (NEW.id, 'create', to_jsonb(NEW));
+    ELSIF TG_OP = 'UPDATE' THEN
+    -- if stataus changed, update reservation_changes
+      IF OLD.status <> NEW.status THEN
+        INSERT INTO rsvp.reservation_changes(reservation_id, op, old, new)
+          VALUES (NEW.id, 'update', to_jsonb(OLD), to_jsonb(NEW));
+      END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+      -- update reservation_changes
+      INSERT INTO rsvp.reservation_changes(reservation_id, op, old) VALUES (OLD.id, 'delete', to_jsonb(OLD));
+    END IF;
+    -- notify a channel called reservation_update
+    NOTIFY reservation_update;
+    RETURN NULL;
+  END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER reservations_trigger
+    AFTER INSERT OR UPDATE OR DELETE ON rsvp.reservations
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE PROCEDURE rsvp.reservations_trigger();
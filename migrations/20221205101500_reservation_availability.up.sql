@@ -0,0 +1,27 @@
+-- free time windows of a resource within the given window. if start or end is null, the window has no bound
+CREATE OR REPLACE FUNCTION rsvp.availability(
+  rid text,
+  _start timestamptz,
+  _end timestamptz,
+  min_duration interval DEFAULT NULL
+) RETURNS SETOF TSTZRANGE AS $$
+DECLARE
+  _during TSTZRANGE;
+BEGIN
+  _during := TSTZRANGE(_start, _end, '[)');
+  RETURN QUERY
+    SELECT slot FROM unnest(
+      -- the window minus all the reserved timespans of the resource
+      tstzmultirange(_during) - (
+        SELECT COALESCE(range_agg(timespan), '{}'::tstzmultirange)
+        FROM rsvp.reservations
+        WHERE resource_id = rid AND timespan && _during
+      )
+    ) AS slot
+    WHERE min_duration IS NULL
+      OR lower_inf(slot)
+      OR upper_inf(slot)
+      OR upper(slot) - lower(slot) >= min_duration
+    ORDER BY lower(slot) NULLS FIRST;
+END;
+$$ LANGUAGE plpgsql;
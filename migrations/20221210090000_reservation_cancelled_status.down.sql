@@ -0,0 +1,3 @@
+-- postgres could not drop a value from an enum type, 'cancelled' is kept in rsvp.reservation_status.
+-- the rows using it are removed by the down migration of reservation_cancel
+SELECT 1;
@@ -0,0 +1,3 @@
+-- the new enum value could only be used after this migration is committed,
+-- so it is added in a migration of its own
+ALTER TYPE rsvp.reservation_status ADD VALUE 'cancelled';
@@ -0,0 +1,135 @@
+DELETE FROM rsvp.reservations WHERE status = 'cancelled';
+
+ALTER TABLE rsvp.reservations
+  DROP CONSTRAINT reservations_conflict,
+  ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&);
+
+DROP FUNCTION rsvp.query;
+DROP FUNCTION rsvp.filter;
+
+ALTER TABLE rsvp.reservations
+  DROP COLUMN cancelled_at,
+  DROP COLUMN cancel_reason;
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+  uid text,
+  rid text,
+  --during TSTZRANGE DEFAULT '(-infinity, infinity)'::TSTZRANGE,
+  _start timestamptz,
+  _end timestamptz,
+  status rsvp.reservation_status DEFAULT 'pending',
+  --page integer DEFAULT 1,
+  is_desc bool DEFAULT FALSE
+  --page_size bigint DEFAULT 10
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _during TSTZRANGE;
+  _sql text;
+BEGIN
+  -- if start or end is null, use infinity
+  _during := TSTZRANGE(
+    COALESCE(_start, '-infinity'),
+    COALESCE(_end, 'infinity'),
+    '[)'
+  );
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %L @> timespan AND status = %L AND %s ORDER BY lower(timespan) %s',
+    _during,
+    status,
+    CASE
+        WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+        WHEN uid IS NULL THEN 'resource_id =' || quote_literal(rid)
+        WHEN rid IS NULL THEN 'user_id =' || quote_literal(uid)
+        ELSE 'user_id =' || quote_literal(uid) || ' AND resource_id =' || quote_literal(rid)
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION rsvp.filter(
+  uid text,
+  rid text,
+  status rsvp.reservation_status,
+  cursor bigint DEFAULT NULL,
+  is_desc bool DEFAULT FALSE,
+  page_size bigint DEFAULT 10
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _sql text;
+  _offset bigint;
+BEGIN
+  -- if page_size is not between 10 and 100, set it to 10
+  IF page_size < 10 OR page_size > 100 THEN
+    page_size := 10;
+  END IF;
+  -- if cursor is null or less than 0, set it to 0 if is_desc if false, or to 2^63-1 if is_des is true
+  IF cursor IS NULL OR cursor < 0 THEN
+    IF is_desc THEN
+      cursor := 9223372036854775807;
+    ELSE
+      cursor := 0;
+    END IF;
+  END IF;
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %s AND status = %L AND %s ORDER BY id %s LIMIT %L::integer',
+    CASE
+      WHEN is_desc THEN 'id <= ' || cursor
+      ELSE 'id >= ' || cursor
+    END,
+    status,
+    CASE
+        WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+        WHEN uid IS NULL THEN 'resource_id =' || quote_literal(rid)
+        WHEN rid IS NULL THEN 'user_id =' || quote_literal(uid)
+        ELSE 'user_id =' || quote_literal(uid) || ' AND resource_id =' || quote_literal(rid)
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    page_size + 1
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+-- free time windows of a resource within the given window. if start or end is null, the window has no bound
+CREATE OR REPLACE FUNCTION rsvp.availability(
+  rid text,
+  _start timestamptz,
+  _end timestamptz,
+  min_duration interval DEFAULT NULL
+) RETURNS SETOF TSTZRANGE AS $$
+DECLARE
+  _during TSTZRANGE;
+BEGIN
+  _during := TSTZRANGE(_start, _end, '[)');
+  RETURN QUERY
+    SELECT slot FROM unnest(
+      -- the window minus all the reserved timespans of the resource
+      tstzmultirange(_during) - (
+        SELECT COALESCE(range_agg(timespan), '{}'::tstzmultirange)
+        FROM rsvp.reservations
+        WHERE resource_id = rid AND timespan && _during
+      )
+    ) AS slot
+    WHERE min_duration IS NULL
+      OR lower_inf(slot)
+      OR upper_inf(slot)
+      OR upper(slot) - lower(slot) >= min_duration
+    ORDER BY lower(slot) NULLS FIRST;
+END;
+$$ LANGUAGE plpgsql;
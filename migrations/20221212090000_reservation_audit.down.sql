@@ -0,0 +1,32 @@
+-- record status, timespan and resource changes only, without audit data
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS
+$$
+  BEGIN
+    -- released when the transaction ends
+    PERFORM pg_advisory_xact_lock(hashtext('rsvp.reservation_changes'));
+    IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes(reservation_id, op, new) VALUES (NEW.id, 'create', to_jsonb(NEW));
+    ELSIF TG_OP = 'UPDATE' THEN
+    -- if status, timespan or resource changed, update reservation_changes
+      IF OLD.status <> NEW.status
+        OR OLD.timespan <> NEW.timespan
+        OR OLD.resource_id <> NEW.resource_id THEN
+        INSERT INTO rsvp.reservation_changes(reservation_id, op, old, new)
+          VALUES (NEW.id, 'update', to_jsonb(OLD), to_jsonb(NEW));
+      END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+      -- update reservation_changes
+      INSERT INTO rsvp.reservation_changes(reservation_id, op, old) VALUES (OLD.id, 'delete', to_jsonb(OLD));
+    END IF;
+    -- notify a channel called reservation_update
+    NOTIFY reservation_update;
+    RETURN NULL;
+  END;
+$$ LANGUAGE plpgsql;
+
+DROP INDEX rsvp.reservation_changes_reservation_id_idx;
+
+ALTER TABLE rsvp.reservation_changes
+  DROP COLUMN changed_at,
+  DROP COLUMN actor;
@@ -0,0 +1,35 @@
+-- turn the change queue into an audit log: when and who, besides the row before/after the change
+ALTER TABLE rsvp.reservation_changes
+  ADD COLUMN changed_at timestamptz NOT NULL DEFAULT now(),
+  ADD COLUMN actor text;
+
+CREATE INDEX reservation_changes_reservation_id_idx ON rsvp.reservation_changes(reservation_id);
+
+-- record every change of a reservation. The actor is read from the transaction local
+-- setting rsvp.actor, set by the application with set_config('rsvp.actor', actor, true)
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS
+$$
+  DECLARE
+    _actor text;
+  BEGIN
+    -- released when the transaction ends
+    PERFORM pg_advisory_xact_lock(hashtext('rsvp.reservation_changes'));
+    _actor := NULLIF(current_setting('rsvp.actor', true), '');
+    IF TG_OP = 'INSERT' THEN
+      INSERT INTO rsvp.reservation_changes(reservation_id, op, actor, new)
+        VALUES (NEW.id, 'create', _actor, to_jsonb(NEW));
+    ELSIF TG_OP = 'UPDATE' THEN
+      -- if anything changed, update reservation_changes
+      IF OLD IS DISTINCT FROM NEW THEN
+        INSERT INTO rsvp.reservation_changes(reservation_id, op, actor, old, new)
+          VALUES (NEW.id, 'update', _actor, to_jsonb(OLD), to_jsonb(NEW));
+      END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+      INSERT INTO rsvp.reservation_changes(reservation_id, op, actor, old)
+        VALUES (OLD.id, 'delete', _actor, to_jsonb(OLD));
+    END IF;
+    -- notify a channel called reservation_update
+    NOTIFY reservation_update;
+    RETURN NULL;
+  END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,152 @@
+-- server maintained timestamps of a reservation
+ALTER TABLE rsvp.reservations
+  ADD COLUMN created_at timestamptz NOT NULL DEFAULT now(),
+  ADD COLUMN updated_at timestamptz NOT NULL DEFAULT now();
+
+CREATE INDEX reservations_created_at_idx ON rsvp.reservations(created_at);
+CREATE INDEX reservations_updated_at_idx ON rsvp.reservations(updated_at);
+
+-- created_at never changes, updated_at is bumped when anything else changes
+CREATE OR REPLACE FUNCTION rsvp.reservations_timestamps() RETURNS TRIGGER AS
+$$
+  BEGIN
+    NEW.created_at := OLD.created_at;
+    NEW.updated_at := OLD.updated_at;
+    IF OLD IS DISTINCT FROM NEW THEN
+      NEW.updated_at := now();
+    END IF;
+    RETURN NEW;
+  END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER reservations_timestamps
+    BEFORE UPDATE ON rsvp.reservations
+    FOR EACH ROW EXECUTE PROCEDURE rsvp.reservations_timestamps();
+
+-- the functions return the rows of rsvp.reservations, recreate them for the new columns
+DROP FUNCTION rsvp.query;
+DROP FUNCTION rsvp.filter;
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+  uid text,
+  rid text,
+  --during TSTZRANGE DEFAULT '(-infinity, infinity)'::TSTZRANGE,
+  _start timestamptz,
+  _end timestamptz,
+  status rsvp.reservation_status DEFAULT 'pending',
+  --page integer DEFAULT 1,
+  is_desc bool DEFAULT FALSE,
+  --page_size bigint DEFAULT 10,
+  include_cancelled bool DEFAULT FALSE,
+  sort_by text DEFAULT 'start',
+  created tstzrange DEFAULT NULL,
+  updated tstzrange DEFAULT NULL
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _during TSTZRANGE;
+  _sql text;
+BEGIN
+  -- if start or end is null, use infinity
+  _during := TSTZRANGE(
+    COALESCE(_start, '-infinity'),
+    COALESCE(_end, 'infinity'),
+    '[)'
+  );
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %L @> timespan AND %s AND %s AND %s AND %s ORDER BY %s %s, id %s',
+    _during,
+    CASE
+      WHEN include_cancelled THEN 'status IN (' || quote_literal(status) || ', ''cancelled'')'
+      ELSE 'status = ' || quote_literal(status)
+    END,
+    CASE
+        WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+        WHEN uid IS NULL THEN 'resource_id =' || quote_literal(rid)
+        WHEN rid IS NULL THEN 'user_id =' || quote_literal(uid)
+        ELSE 'user_id =' || quote_literal(uid) || ' AND resource_id =' || quote_literal(rid)
+    END,
+    CASE
+      WHEN created IS NULL THEN 'TRUE'
+      ELSE quote_literal(created) || '::tstzrange @> created_at'
+    END,
+    CASE
+      WHEN updated IS NULL THEN 'TRUE'
+      ELSE quote_literal(updated) || '::tstzrange @> updated_at'
+    END,
+    CASE sort_by
+      WHEN 'id' THEN 'id'
+      WHEN 'created_at' THEN 'created_at'
+      WHEN 'updated_at' THEN 'updated_at'
+      ELSE 'lower(timespan)'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION rsvp.filter(
+  uid text,
+  rid text,
+  status rsvp.reservation_status,
+  cursor bigint DEFAULT NULL,
+  is_desc bool DEFAULT FALSE,
+  page_size bigint DEFAULT 10,
+  include_cancelled bool DEFAULT FALSE
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _sql text;
+  _offset bigint;
+BEGIN
+  -- if page_size is not between 10 and 100, set it to 10
+  IF page_size < 10 OR page_size > 100 THEN
+    page_size := 10;
+  END IF;
+  -- if cursor is null or less than 0, set it to 0 if is_desc if false, or to 2^63-1 if is_des is true
+  IF cursor IS NULL OR cursor < 0 THEN
+    IF is_desc THEN
+      cursor := 9223372036854775807;
+    ELSE
+      cursor := 0;
+    END IF;
+  END IF;
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %s AND %s AND %s ORDER BY id %s LIMIT %L::integer',
+    CASE
+      WHEN is_desc THEN 'id <= ' || cursor
+      ELSE 'id >= ' || cursor
+    END,
+    CASE
+      WHEN include_cancelled THEN 'status IN (' || quote_literal(status) || ', ''cancelled'')'
+      ELSE 'status = ' || quote_literal(status)
+    END,
+    CASE
+        WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+        WHEN uid IS NULL THEN 'resource_id =' || quote_literal(rid)
+        WHEN rid IS NULL THEN 'user_id =' || quote_literal(uid)
+        ELSE 'user_id =' || quote_literal(uid) || ' AND resource_id =' || quote_literal(rid)
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    page_size + 1
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
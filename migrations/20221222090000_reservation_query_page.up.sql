@@ -0,0 +1,95 @@
+-- keyset pagination of the query, ordered by start time with the reservation id as tiebreak
+DROP FUNCTION rsvp.query;
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+  uid text,
+  rid text,
+  --during TSTZRANGE DEFAULT '(-infinity, infinity)'::TSTZRANGE,
+  _start timestamptz,
+  _end timestamptz,
+  statuses rsvp.reservation_status[] DEFAULT '{}',
+  --page integer DEFAULT 1,
+  is_desc bool DEFAULT FALSE,
+  --page_size bigint DEFAULT 10,
+  include_cancelled bool DEFAULT FALSE,
+  sort_by text DEFAULT 'start',
+  created tstzrange DEFAULT NULL,
+  updated tstzrange DEFAULT NULL,
+  match_mode text DEFAULT 'contained',
+  page_size bigint DEFAULT NULL,
+  after_start timestamptz DEFAULT NULL,
+  after_id bigint DEFAULT NULL
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _during TSTZRANGE;
+  _sql text;
+BEGIN
+  -- no status means any status
+  statuses := COALESCE(statuses, '{}');
+  -- if start or end is null, use infinity
+  _during := TSTZRANGE(
+    COALESCE(_start, '-infinity'),
+    COALESCE(_end, 'infinity'),
+    '[)'
+  );
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %s AND %s AND %s AND %s AND %s AND %s ORDER BY %s %s, id %s %s',
+    CASE match_mode
+      WHEN 'overlapping' THEN quote_literal(_during) || '::tstzrange && timespan'
+      WHEN 'starting_within' THEN quote_literal(_during) || '::tstzrange @> lower(timespan)'
+      -- the end of a reservation is exclusive, so it ends within (start, end]
+      WHEN 'ending_within' THEN quote_literal(TSTZRANGE(lower(_during), upper(_during), '(]')) || '::tstzrange @> upper(timespan)'
+      ELSE quote_literal(_during) || '::tstzrange @> timespan'
+    END,
+    -- paged queries are ordered by start time and id, so the next page starts after the last row
+    CASE
+      WHEN after_id IS NULL THEN 'TRUE'
+      WHEN is_desc THEN format('(lower(timespan), id) < (%L::timestamptz, %s)', after_start, after_id)
+      ELSE format('(lower(timespan), id) > (%L::timestamptz, %s)', after_start, after_id)
+    END,
+    CASE
+      WHEN cardinality(statuses) = 0 AND include_cancelled THEN 'TRUE'
+      WHEN cardinality(statuses) = 0 THEN 'status <> ''cancelled'''
+      WHEN include_cancelled THEN 'status = ANY(' || quote_literal(array_append(statuses, 'cancelled')) || '::rsvp.reservation_status[])'
+      ELSE 'status = ANY(' || quote_literal(statuses) || '::rsvp.reservation_status[])'
+    END,
+    CASE
+        WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+        WHEN uid IS NULL THEN 'resource_id =' || quote_literal(rid)
+        WHEN rid IS NULL THEN 'user_id =' || quote_literal(uid)
+        ELSE 'user_id =' || quote_literal(uid) || ' AND resource_id =' || quote_literal(rid)
+    END,
+    CASE
+      WHEN created IS NULL THEN 'TRUE'
+      ELSE quote_literal(created) || '::tstzrange @> created_at'
+    END,
+    CASE
+      WHEN updated IS NULL THEN 'TRUE'
+      ELSE quote_literal(updated) || '::tstzrange @> updated_at'
+    END,
+    CASE sort_by
+      WHEN 'id' THEN 'id'
+      WHEN 'created_at' THEN 'created_at'
+      WHEN 'updated_at' THEN 'updated_at'
+      ELSE 'lower(timespan)'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    CASE
+      WHEN page_size IS NULL THEN ''
+      ELSE 'LIMIT ' || page_size
+    END
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,91 @@
+-- sort query results by end time
+CREATE OR REPLACE FUNCTION rsvp.query(
+  uid text,
+  rid text,
+  --during TSTZRANGE DEFAULT '(-infinity, infinity)'::TSTZRANGE,
+  _start timestamptz,
+  _end timestamptz,
+  statuses rsvp.reservation_status[] DEFAULT '{}',
+  --page integer DEFAULT 1,
+  is_desc bool DEFAULT FALSE,
+  --page_size bigint DEFAULT 10,
+  include_cancelled bool DEFAULT FALSE,
+  sort_by text DEFAULT 'start',
+  created tstzrange DEFAULT NULL,
+  updated tstzrange DEFAULT NULL,
+  match_mode text DEFAULT 'contained',
+  page_size bigint DEFAULT NULL,
+  after_start timestamptz DEFAULT NULL,
+  after_id bigint DEFAULT NULL
+) RETURNS TABLE (LIKE rsvp.reservations) AS $$
+DECLARE
+  _during TSTZRANGE;
+  _ends TSTZRANGE;
+  _sql text;
+BEGIN
+  -- no status means any status
+  statuses := COALESCE(statuses, '{}');
+  IF include_cancelled AND cardinality(statuses) > 0 THEN
+    statuses := array_append(statuses, 'cancelled');
+  END IF;
+  -- if start or end is null, use infinity
+  _during := TSTZRANGE(
+    COALESCE(_start, '-infinity'),
+    COALESCE(_end, 'infinity'),
+    '[)'
+  );
+  -- the end of a reservation is exclusive, so it ends within (start, end]
+  _ends := TSTZRANGE(lower(_during), upper(_during), '(]');
+  -- paged queries are ordered by start time and id, so the next page starts after the last row
+  IF page_size IS NOT NULL THEN
+    sort_by := 'start';
+  END IF;
+  -- format the query based on parameters
+  _sql := format(
+    'SELECT * FROM rsvp.reservations WHERE %s AND %s AND %s'
+    ' AND ($3::text IS NULL OR user_id = $3) AND ($4::text IS NULL OR resource_id = $4)'
+    ' AND ($5::tstzrange IS NULL OR $5 @> created_at) AND ($6::tstzrange IS NULL OR $6 @> updated_at)'
+    ' ORDER BY %s %s, id %s %s',
+    CASE match_mode
+      WHEN 'overlapping' THEN '$1 && timespan'
+      WHEN 'starting_within' THEN '$1 @> lower(timespan)'
+      WHEN 'ending_within' THEN '$2 @> upper(timespan)'
+      ELSE '$1 @> timespan'
+    END,
+    CASE
+      WHEN cardinality(statuses) = 0 AND include_cancelled THEN 'TRUE'
+      WHEN cardinality(statuses) = 0 THEN 'status <> ''cancelled'''
+      ELSE 'status = ANY($7)'
+    END,
+    CASE
+      WHEN after_id IS NULL THEN 'TRUE'
+      WHEN is_desc THEN '(lower(timespan), id) < ($8, $9)'
+      ELSE '(lower(timespan), id) > ($8, $9)'
+    END,
+    CASE sort_by
+      WHEN 'id' THEN 'id'
+      WHEN 'created_at' THEN 'created_at'
+      WHEN 'updated_at' THEN 'updated_at'
+      WHEN 'end' THEN 'upper(timespan)'
+      ELSE 'lower(timespan)'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    CASE
+      WHEN is_desc THEN 'DESC'
+      ELSE 'ASC'
+    END,
+    CASE
+      WHEN page_size IS NULL THEN ''
+      ELSE 'LIMIT $10'
+    END
+  );
+  -- log the sql
+  --RAISE NOTICE '%s', _sql;
+  -- execute the query
+  RETURN QUERY EXECUTE _sql
+    USING _during, _ends, uid, rid, created, updated, statuses, after_start, after_id, page_size;
+END;
+$$ LANGUAGE plpgsql;
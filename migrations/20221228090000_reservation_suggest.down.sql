@@ -0,0 +1 @@
+DROP FUNCTION rsvp.suggest;
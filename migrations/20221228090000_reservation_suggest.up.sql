@@ -0,0 +1,33 @@
+-- free windows of the given duration on a resource, nearest to the given start time first.
+-- each free gap offers the window closest to the start from either side of it
+CREATE OR REPLACE FUNCTION rsvp.suggest(
+  rid text,
+  _start timestamptz,
+  duration interval,
+  n integer
+) RETURNS SETOF TSTZRANGE AS $$
+BEGIN
+  RETURN QUERY
+    SELECT slot FROM (
+      SELECT DISTINCT TSTZRANGE(c.start, c.start + duration, '[)') AS slot
+      FROM unnest(
+        -- all the time minus the reserved timespans of the resource
+        tstzmultirange(TSTZRANGE('-infinity', 'infinity', '[)')) - (
+          SELECT COALESCE(range_agg(timespan), '{}'::tstzmultirange)
+          FROM rsvp.reservations
+          WHERE resource_id = rid AND status <> 'cancelled'
+        )
+      ) AS gap,
+      -- the earliest window not before the start, and the latest window not after it
+      LATERAL (VALUES
+        (GREATEST(lower(gap), _start)),
+        (LEAST(upper(gap), _start + duration) - duration)
+      ) AS c(start)
+      WHERE c.start >= lower(gap) AND c.start + duration <= upper(gap)
+    ) AS candidates
+    ORDER BY
+      CASE WHEN lower(slot) >= _start THEN lower(slot) - _start ELSE _start - lower(slot) END,
+      lower(slot)
+    LIMIT n;
+END;
+$$ LANGUAGE plpgsql;
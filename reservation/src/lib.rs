@@ -2,28 +2,62 @@ mod manager;
 use abi::{FilterPager, ReservationId};
 use async_trait::async_trait;
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, Mutex};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReservationManager {
     pool: PgPool,
+    // wakes up the listen streams when reservations change. All the streams share one listening
+    // connection, set up by the first stream and released after the last one is gone
+    changes: Arc<Mutex<Option<broadcast::Sender<()>>>>,
+    // who makes the changes, recorded in the reservation history
+    actor: Option<String>,
 }
 #[async_trait]
 pub trait Rsvp {
     /// make a reservation
     async fn reserve(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error>;
-    /// change a reservation stataus (if current reservation stataus is pending, change it to confirmed)
-    async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error>;
+    /// make several reservations in one transaction, either all of them are made or none is
+    async fn reserve_many(
+        &self,
+        rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// make a reservation, if it conflicts, suggest the free windows asked in the request
+    async fn reserve_or_suggest(
+        &self,
+        request: abi::ReserveRequest,
+    ) -> Result<abi::Reservation, abi::Error>;
+    /// confirm a pending reservation, a confirmed one is returned as is. Any other status is an invalid transition
+    async fn change_status(
+        &self,
+        request: abi::ConfirmRequest,
+    ) -> Result<abi::Reservation, abi::Error>;
+    /// move a reservation to another status, following the status state machine
+    async fn transition(
+        &self,
+        id: ReservationId,
+        status: abi::ReservationStatus,
+    ) -> Result<abi::Reservation, abi::Error>;
     /// update note
     async fn update_note(
         &self,
         id: ReservationId,
         note: String,
     ) -> Result<abi::Reservation, abi::Error>;
+    /// update the masked fields of a reservation
+    async fn update(&self, request: abi::UpdateRequest) -> Result<abi::Reservation, abi::Error>;
+    /// move a reservation to another time window or resource
+    async fn reschedule(
+        &self,
+        request: abi::RescheduleRequest,
+    ) -> Result<abi::Reservation, abi::Error>;
+    /// cancel reservation, the reservation is kept with cancelled status
+    async fn cancel(&self, request: abi::CancelRequest) -> Result<abi::Reservation, abi::Error>;
     /// delete reservation
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error>;
     /// get reservation by id
-    async fn get(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error>;
+    async fn get(&self, request: abi::GetRequest) -> Result<abi::Reservation, abi::Error>;
 
     /// query reservations
     async fn query(
@@ -31,9 +65,47 @@ pub trait Rsvp {
         query: abi::ReservationQuery,
     ) -> mpsc::Receiver<Result<abi::Reservation, abi::Error>>;
 
+    /// query a page of reservations ordered by start time, with the token of the next page
+    async fn query_page(
+        &self,
+        query: abi::ReservationQuery,
+    ) -> Result<(Option<String>, Vec<abi::Reservation>), abi::Error>;
+
     /// query reservations ordered by reservation id
     async fn filter(
         &self,
         filter: abi::ReservationFilter,
     ) -> Result<(FilterPager, Vec<abi::Reservation>), abi::Error>;
+
+    /// get free time windows of a resource
+    async fn availability(
+        &self,
+        request: abi::AvailabilityRequest,
+    ) -> Result<Vec<abi::TimeSlot>, abi::Error>;
+
+    /// find the earliest free slot from a pool of resources, reserve it if asked
+    async fn find_slot(
+        &self,
+        request: abi::FindSlotRequest,
+    ) -> Result<(abi::TimeSlot, Option<abi::Reservation>), abi::Error>;
+
+    /// check if a reservation could be made without making it, return the conflicts found
+    async fn check(
+        &self,
+        rsvp: abi::Reservation,
+    ) -> Result<Vec<abi::ReservationConflict>, abi::Error>;
+
+    /// confirm, cancel or note all the reservations selected by the request in one transaction,
+    /// return the reservations changed. for a dry run, nothing is changed
+    async fn bulk(&self, request: abi::BulkRequest) -> Result<Vec<abi::Reservation>, abi::Error>;
+
+    /// get the audit history of a reservation, oldest first. The actors are recorded as given to
+    /// `ReservationManager::with_actor`, they are not verified
+    async fn history(&self, id: ReservationId) -> Result<Vec<abi::ReservationChange>, abi::Error>;
+
+    /// listen to reservation changes, driven by the notifications of the reservation_changes queue
+    async fn listen(
+        &self,
+        request: abi::ListenRequest,
+    ) -> mpsc::Receiver<Result<abi::ListenResponse, abi::Error>>;
 }
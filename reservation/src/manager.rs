@@ -1,53 +1,177 @@
+use std::collections::HashMap;
+
 use crate::{ReservationManager, Rsvp};
-use abi::{convert_to_utc_time, DbConfig, FilterPager, Normalizer, Reservation, ToSql};
-use abi::{ReservationId, Validator};
+use abi::{
+    bulk_request::Selector, ListenRequest, ListenResponse, ReservationId, ReservationUpdateType,
+    RsvpUpdateType, Validator,
+};
+use abi::{
+    convert_to_interval, convert_to_timestamp, convert_to_utc_time, DbConfig, FilterPager,
+    Normalizer,
+};
+use abi::{
+    BatchConflict, Reservation, ReservationChange, ReservationConflict, ReservationWindow,
+    TimeSlot, ToSql,
+};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
 use futures::stream::StreamExt;
 use sqlx::postgres::types::PgRange;
-use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::{PgArguments, PgListener, PgPoolOptions};
+use sqlx::query::QueryAs;
 
-use sqlx::Row;
-use sqlx::{Either, PgPool};
-use tokio::sync::mpsc;
+use sqlx::{Acquire, Either, PgConnection, PgExecutor, PgPool, Postgres, Transaction};
+use sqlx::{FromRow, Row};
+use tokio::sync::{broadcast, mpsc};
 use tracing::{info, warn};
 
 #[async_trait]
 impl Rsvp for ReservationManager {
-    async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
-        rsvp.validate()?;
-
-        let status = abi::ReservationStatus::from_i32(rsvp.status)
-            .unwrap_or(abi::ReservationStatus::Pending);
+    async fn reserve(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
+        let status = new_status(&rsvp)?;
         let timespan: PgRange<DateTime<Utc>> = rsvp.get_timespan();
 
         // generate a insert sql for the reservation
         // execute the insert sql
-        let id = sqlx::query(
-      "INSERT INTO rsvp.reservations (user_id, resource_id, timespan, note, status) VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status) RETURNING id"
-    )
-    .bind(rsvp.user_id.clone())
-    .bind(rsvp.resource_id.clone())
-    .bind(timespan)
-    .bind(rsvp.note.clone())
-    .bind(status.to_string())
-    .fetch_one(&self.pool)
-    .await?
-    .get(0);
-        rsvp.id = id;
+        let mut tx = self.begin().await?;
+        let ret = insert(&mut tx, &rsvp, status).await;
+        let rsvp = match ret {
+            Ok(rsvp) => rsvp,
+            Err(e) => {
+                drop(tx);
+                let rid = Some(rsvp.resource_id.as_str());
+                return Err(self.with_existing(e.into(), rid, timespan, None).await);
+            }
+        };
+        tx.commit().await?;
         Ok(rsvp)
     }
-    async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
-        // is current status is pending, change it to confirmed, otherwise do nothing
-        id.validate()?;
-        let rsvp = sqlx::query_as(
-      "UPDATE rsvp.reservations SET status = 'confirmed' WHERE id = $1 AND status = 'pending' RETURNING *"
-    )
-    .bind(id)
-    .fetch_one(&self.pool)
-    .await?;
-        Ok(rsvp)
+    async fn reserve_many(
+        &self,
+        rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        let statuses = rsvps
+            .iter()
+            .map(new_status)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut tx = self.begin().await?;
+        // the reservations made so far, with their index in the batch
+        let mut reserved: Vec<(usize, Reservation)> = Vec::with_capacity(rsvps.len());
+        let mut conflicts = vec![];
+        for (index, (rsvp, status)) in rsvps.iter().zip(statuses).enumerate() {
+            // insert under a savepoint, so a conflict doesn't abort the transaction and the rest
+            // of the batch could still be checked
+            let mut savepoint = tx.begin().await?;
+            match insert(&mut savepoint, rsvp, status)
+                .await
+                .map_err(abi::Error::from)
+            {
+                Ok(r) => {
+                    savepoint.commit().await?;
+                    reserved.push((index, r));
+                }
+                Err(abi::Error::ConflictReservation { .. }) => {
+                    savepoint.rollback().await?;
+                    let timespan = rsvp.get_timespan();
+                    let existing = fetch_existing(&mut tx, Some(&rsvp.resource_id), timespan, None)
+                        .await?
+                        .into_iter()
+                        .map(
+                            |r| match reserved.iter().find(|(_, made)| made.id == r.id) {
+                                // made in this batch, it will be rolled back
+                                Some((i, _)) => rsvps[*i].clone(),
+                                None => r,
+                            },
+                        )
+                        .collect();
+                    conflicts.push(BatchConflict {
+                        index,
+                        new: rsvp.get_window(),
+                        existing,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !conflicts.is_empty() {
+            tx.rollback().await?;
+            return Err(abi::Error::ConflictReservations(conflicts));
+        }
+        tx.commit().await?;
+        Ok(reserved.into_iter().map(|(_, r)| r).collect())
+    }
+
+    async fn reserve_or_suggest(
+        &self,
+        request: abi::ReserveRequest,
+    ) -> Result<abi::Reservation, abi::Error> {
+        request.validate()?;
+        let rsvp = request.reservation.unwrap_or_default();
+        let (resource_id, start, end) = (
+            rsvp.resource_id.clone(),
+            rsvp.start.clone(),
+            rsvp.end.clone(),
+        );
+        match self.reserve(rsvp).await {
+            Err(abi::Error::ConflictReservation { info, existing, .. })
+                if request.suggestions > 0 =>
+            {
+                let start = convert_to_utc_time(start.unwrap());
+                let duration = convert_to_utc_time(end.unwrap()) - start;
+                let slots: Vec<PgRange<DateTime<Utc>>> =
+                    sqlx::query_scalar("SELECT * FROM rsvp.suggest($1, $2, $3, $4)")
+                        .bind(&resource_id)
+                        .bind(start)
+                        .bind(duration)
+                        .bind(request.suggestions as i32)
+                        .fetch_all(&self.pool)
+                        .await?;
+                let suggestions = slots
+                    .into_iter()
+                    .map(|slot| TimeSlot::new(&resource_id, slot))
+                    .collect();
+                Err(abi::Error::ConflictReservation {
+                    info,
+                    existing,
+                    suggestions,
+                })
+            }
+            ret => ret,
+        }
+    }
+
+    async fn change_status(
+        &self,
+        request: abi::ConfirmRequest,
+    ) -> Result<abi::Reservation, abi::Error> {
+        // is current status is pending, change it to confirmed, if it is confirmed, do nothing
+        self.set_status(
+            request.id,
+            abi::ReservationStatus::Confirmed,
+            "",
+            request.expected_version,
+        )
+        .await
+    }
+
+    async fn transition(
+        &self,
+        id: ReservationId,
+        status: abi::ReservationStatus,
+    ) -> Result<abi::Reservation, abi::Error> {
+        self.set_status(id, status, "", None).await
+    }
+
+    async fn cancel(&self, request: abi::CancelRequest) -> Result<abi::Reservation, abi::Error> {
+        // keep the reservation for history, it won't block the resource any more
+        self.set_status(
+            request.id,
+            abi::ReservationStatus::Cancelled,
+            &request.reason,
+            request.expected_version,
+        )
+        .await
     }
 
     async fn update_note(
@@ -57,32 +181,115 @@ impl Rsvp for ReservationManager {
     ) -> Result<abi::Reservation, abi::Error> {
         // update the note of the reservation
         id.validate()?;
+        let mut tx = self.begin().await?;
         let rsvp =
             sqlx::query_as("UPDATE rsvp.reservations SET note = $1 WHERE id = $2 RETURNING *")
                 .bind(note)
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut tx)
                 .await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
 
-    async fn get(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
-        // get the reservation by id
-        id.validate()?;
-        let rsvp = sqlx::query_as("SELECT * FROM rsvp.reservations WHERE id = $1")
-            .bind(id)
-            .fetch_one(&self.pool)
-            .await?;
+    async fn update(&self, request: abi::UpdateRequest) -> Result<abi::Reservation, abi::Error> {
+        // lock the reservation, apply the masked fields on it and write it back.
+        // the exclusion constraint rejects it if the new window is taken
+        request.validate()?;
+        let mut tx = self.begin().await?;
+        // a cancelled reservation is kept for history only, it could not be changed
+        let rsvp: abi::Reservation = sqlx::query_as(
+            "SELECT * FROM rsvp.reservations WHERE id = $1 AND status <> 'cancelled' FOR UPDATE",
+        )
+        .bind(request.id)
+        .fetch_one(&mut tx)
+        .await?;
+        rsvp.check_version(request.expected_version)?;
+        let rsvp = request.apply(rsvp)?;
+        let status = abi::ReservationStatus::from_i32(rsvp.status)
+            .ok_or(abi::Error::InvalidStatus(rsvp.status))?;
+        let ret = sqlx::query_as(
+            "UPDATE rsvp.reservations SET user_id = $1, resource_id = $2, timespan = $3, note = $4, status = $5::rsvp.reservation_status WHERE id = $6 RETURNING *",
+        )
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(&rsvp.note)
+        .bind(status.to_string())
+        .bind(rsvp.id)
+        .fetch_one(&mut tx)
+        .await;
+        let updated = match ret {
+            Ok(updated) => updated,
+            Err(e) => {
+                drop(tx);
+                let rid = Some(rsvp.resource_id.as_str());
+                return Err(self
+                    .with_existing(e.into(), rid, rsvp.get_timespan(), Some(rsvp.id))
+                    .await);
+            }
+        };
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    async fn reschedule(
+        &self,
+        request: abi::RescheduleRequest,
+    ) -> Result<abi::Reservation, abi::Error> {
+        // move the reservation to the new timespan, and to the new resource if given.
+        // the exclusion constraint rejects it if the new window is taken
+        request.validate()?;
+        let resource_id = if request.resource_id.is_empty() {
+            None
+        } else {
+            Some(request.resource_id.clone())
+        };
+        let mut tx = self.begin().await?;
+        let ret = sqlx::query_as(
+            "UPDATE rsvp.reservations SET timespan = $1, resource_id = COALESCE($2, resource_id) WHERE id = $3 AND status <> 'cancelled' RETURNING *",
+        )
+        .bind(request.get_timespan())
+        .bind(&resource_id)
+        .bind(request.id)
+        .fetch_one(&mut tx)
+        .await;
+        let rsvp = match ret {
+            Ok(rsvp) => rsvp,
+            Err(e) => {
+                drop(tx);
+                let rid = resource_id.as_deref();
+                return Err(self
+                    .with_existing(e.into(), rid, request.get_timespan(), Some(request.id))
+                    .await);
+            }
+        };
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn get(&self, request: abi::GetRequest) -> Result<abi::Reservation, abi::Error> {
+        // get the reservation by id, cancelled reservation is returned only if asked
+        request.id.validate()?;
+        let rsvp = sqlx::query_as(
+            "SELECT * FROM rsvp.reservations WHERE id = $1 AND ($2 OR status <> 'cancelled')",
+        )
+        .bind(request.id)
+        .bind(request.include_cancelled)
+        .fetch_one(&self.pool)
+        .await?;
         Ok(rsvp)
     }
 
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
         // delete the reservation by id
         id.validate()?;
+        let mut tx = self.begin().await?;
         let rsvp = sqlx::query_as("DELETE FROM rsvp.reservations WHERE id = $1 RETURNING *")
             .bind(id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut tx)
             .await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
 
@@ -90,26 +297,19 @@ impl Rsvp for ReservationManager {
         &self,
         query: abi::ReservationQuery,
     ) -> mpsc::Receiver<Result<abi::Reservation, abi::Error>> {
-        let user_id = string_to_option(&query.user_id);
-        let resource_id = string_to_option(&query.resource_id);
-        let start = query.start.map(convert_to_utc_time);
-        let end = query.end.map(convert_to_utc_time);
-        //let range: PgRange<DateTime<Utc>> = query.get_timespan();
-        let status = abi::ReservationStatus::from_i32(query.status)
-            .unwrap_or(abi::ReservationStatus::Pending);
+        let page_size = query.get_page_size();
+        let sql = page_size.and_then(|size| query_sql(QUERY_SQL, &query, size));
         let pool = self.pool.clone();
         let (tx, rx) = mpsc::channel(128);
         tokio::spawn(async move {
-            let mut rsvps = sqlx::query_as(
-                "SELECT * FROM rsvp.query($1,$2, $3, $4, $5::rsvp.reservation_status, $6)",
-            )
-            .bind(user_id)
-            .bind(resource_id)
-            .bind(start)
-            .bind(end)
-            .bind(status.to_string())
-            .bind(query.desc)
-            .fetch_many(&pool);
+            let sql = match sql {
+                Ok(sql) => sql,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let mut rsvps = sql.fetch_many(&pool);
 
             while let Some(ret) = rsvps.next().await {
                 match ret {
@@ -134,23 +334,444 @@ impl Rsvp for ReservationManager {
         });
         rx
     }
+    async fn query_page(
+        &self,
+        query: abi::ReservationQuery,
+    ) -> Result<(Option<String>, Vec<abi::Reservation>), abi::Error> {
+        let page_size = query.get_page_size()?;
+        // fetch one more row to know if there is a next page
+        let mut rsvps = query_sql(QUERY_SQL, &query, page_size.map(|size| size + 1))?
+            .fetch_all(&self.pool)
+            .await?;
+        let token = match page_size {
+            Some(size) if rsvps.len() as i64 > size => {
+                rsvps.truncate(size as usize);
+                rsvps.last().map(abi::ReservationQuery::next_page_token)
+            }
+            _ => None,
+        };
+        Ok((token, rsvps))
+    }
     async fn filter(
         &self,
         mut filter: abi::ReservationFilter,
     ) -> Result<(FilterPager, Vec<abi::Reservation>), abi::Error> {
         filter.normalize()?;
-        let sql = filter.to_sql()?;
-        let rsvps: Vec<Reservation> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let mut sql = filter.to_sql()?;
+        // the page and its total are read in the same snapshot, so they match
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut tx)
+            .await?;
+        let rsvps: Vec<Reservation> = sql.build_query_as().fetch_all(&mut tx).await?;
+        let total = match abi::ReservationTotalMode::from_i32(filter.total_mode).unwrap_or_default()
+        {
+            abi::ReservationTotalMode::None => None,
+            abi::ReservationTotalMode::Exact => {
+                let (count,): (i64,) = filter
+                    .to_count_sql()
+                    .build_query_as()
+                    .fetch_one(&mut tx)
+                    .await?;
+                Some(count)
+            }
+            abi::ReservationTotalMode::Estimate => {
+                let (plan,): (String,) = filter
+                    .to_estimate_sql()
+                    .build_query_as()
+                    .fetch_one(&mut tx)
+                    .await?;
+                plan_rows(&plan)
+            }
+        };
+        tx.commit().await?;
         let mut rsvps = rsvps.into_iter().collect();
-        let pager = filter.get_pager(&mut rsvps)?;
+        let pager = filter.get_pager(&mut rsvps, total)?;
 
         Ok((pager, rsvps.into_iter().collect()))
     }
+
+    async fn availability(
+        &self,
+        request: abi::AvailabilityRequest,
+    ) -> Result<Vec<TimeSlot>, abi::Error> {
+        request.validate()?;
+        let slots: Vec<PgRange<DateTime<Utc>>> =
+            sqlx::query_scalar("SELECT * FROM rsvp.availability($1, $2, $3, $4)")
+                .bind(&request.resource_id)
+                .bind(request.start.map(convert_to_utc_time))
+                .bind(request.end.map(convert_to_utc_time))
+                .bind(request.min_duration.map(convert_to_interval))
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(slots
+            .into_iter()
+            .map(|slot| TimeSlot::new(&request.resource_id, slot))
+            .collect())
+    }
+
+    async fn find_slot(
+        &self,
+        request: abi::FindSlotRequest,
+    ) -> Result<(TimeSlot, Option<Reservation>), abi::Error> {
+        request.validate()?;
+        // other callers may take the slot before us, the exclusion constraint will reject our
+        // reservation then, so we just try the next slot
+        for _ in 0..FIND_SLOT_ATTEMPTS {
+            let slot = self.earliest_slot(&request).await?;
+            if !request.reserve {
+                return Ok((slot, None));
+            }
+            match self.reserve(request.to_reservation(&slot)).await {
+                Ok(rsvp) => return Ok((slot, Some(rsvp))),
+                Err(abi::Error::ConflictReservation { info, .. }) => {
+                    info!("Slot is taken, try next one: {:?}", info);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(abi::Error::NoAvailableSlot)
+    }
+
+    async fn check(&self, rsvp: Reservation) -> Result<Vec<ReservationConflict>, abi::Error> {
+        rsvp.validate()?;
+        let windows: Vec<(String, PgRange<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT resource_id, timespan FROM rsvp.reservations WHERE resource_id = $1 AND timespan && $2 AND status <> 'cancelled' ORDER BY lower(timespan)",
+        )
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let new = rsvp.get_window();
+        Ok(windows
+            .into_iter()
+            .map(|(rid, timespan)| ReservationConflict {
+                new: new.clone(),
+                old: ReservationWindow::new(rid, timespan),
+            })
+            .collect())
+    }
+
+    async fn bulk(&self, request: abi::BulkRequest) -> Result<Vec<abi::Reservation>, abi::Error> {
+        request.validate()?;
+        let action = request.get_action()?;
+        // lock the selected reservations, so they could not change under us
+        let mut tx = self.begin().await?;
+        let selected: Vec<Reservation> = match request.selector.clone() {
+            Some(Selector::Filter(mut filter)) => {
+                // validated with the request, the page fields don't apply to a selector
+                filter.do_normalize();
+                filter
+                    .to_lock_sql()
+                    .build_query_as()
+                    .fetch_all(&mut tx)
+                    .await?
+            }
+            Some(Selector::Query(query)) => {
+                query_sql(QUERY_LOCK_SQL, &query, None)?
+                    .fetch_all(&mut tx)
+                    .await?
+            }
+            None => return Err(abi::Error::InvalidSelector),
+        };
+
+        // skip the reservations already in the target state, the rest must be able to change
+        let status = match action {
+            abi::BulkAction::Confirm => Some(abi::ReservationStatus::Confirmed),
+            abi::BulkAction::Cancel => Some(abi::ReservationStatus::Cancelled),
+            _ => None,
+        };
+        let mut ids = vec![];
+        for rsvp in &selected {
+            let changed = match status {
+                Some(status) => rsvp.status().transition(status)? != rsvp.status(),
+                None => rsvp.note != request.note,
+            };
+            if changed {
+                ids.push(rsvp.id);
+            }
+        }
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut changed: Vec<Reservation> = match status {
+            Some(status) => {
+                sqlx::query_as(
+                    "UPDATE rsvp.reservations SET status = $1::rsvp.reservation_status,
+                    cancelled_at = CASE WHEN $1 = 'cancelled' THEN now() END,
+                    cancel_reason = CASE WHEN $1 = 'cancelled' THEN NULLIF($2, '') END
+                    WHERE id = ANY($3) RETURNING *",
+                )
+                .bind(status.to_string())
+                .bind(&request.note)
+                .bind(&ids)
+                .fetch_all(&mut tx)
+                .await?
+            }
+            None => {
+                sqlx::query_as(
+                    "UPDATE rsvp.reservations SET note = $1 WHERE id = ANY($2) RETURNING *",
+                )
+                .bind(&request.note)
+                .bind(&ids)
+                .fetch_all(&mut tx)
+                .await?
+            }
+        };
+        changed.sort_by_key(|r| r.id);
+        // a dry run makes the changes as usual to show them, then rolls them back
+        if request.dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(changed)
+    }
+
+    async fn history(&self, id: ReservationId) -> Result<Vec<ReservationChange>, abi::Error> {
+        id.validate()?;
+        let changes: Vec<(i64, RsvpUpdateType, DateTime<Utc>, Option<String>)> = sqlx::query_as(
+            "SELECT id::bigint, op, changed_at, actor FROM rsvp.reservation_changes WHERE reservation_id = $1 ORDER BY id",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        if changes.is_empty() {
+            return Err(abi::Error::NotFound);
+        }
+
+        // decode the old/new row snapshots back to reservations, one row per snapshot
+        let sql = format!(
+            "SELECT c.id::bigint AS change_id, s.side, r.* FROM rsvp.reservation_changes c
+            CROSS JOIN LATERAL (VALUES ('old', c.old), ('new', c.new)) AS s(side, snapshot)
+            CROSS JOIN LATERAL {SNAPSHOT_SQL} AS r
+            WHERE c.reservation_id = $1 AND s.snapshot IS NOT NULL"
+        );
+        let rows = sqlx::query(&sql).bind(id).fetch_all(&self.pool).await?;
+        let mut snapshots = HashMap::new();
+        for row in rows {
+            let change_id: i64 = row.get("change_id");
+            let side: String = row.get("side");
+            snapshots.insert((change_id, side), Reservation::from_row(&row)?);
+        }
+
+        Ok(changes
+            .into_iter()
+            .map(|(change_id, op, changed_at, actor)| ReservationChange {
+                id: change_id,
+                reservation_id: id,
+                op: ReservationUpdateType::from(op) as i32,
+                changed_at: Some(convert_to_timestamp(changed_at)),
+                actor: actor.unwrap_or_default(),
+                old: snapshots.remove(&(change_id, "old".to_string())),
+                new: snapshots.remove(&(change_id, "new".to_string())),
+            })
+            .collect())
+    }
+
+    async fn listen(
+        &self,
+        request: ListenRequest,
+    ) -> mpsc::Receiver<Result<ListenResponse, abi::Error>> {
+        let manager = self.clone();
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(128);
+        // listen before reading the last change id, so we won't miss anything in between
+        let subscribed = async {
+            request.validate()?;
+            let changes = self.subscribe().await?;
+            let last_id = match request.since_change_id {
+                Some(id) => id,
+                None => last_change_id(&pool).await?,
+            };
+            Ok::<_, abi::Error>((changes, last_id))
+        };
+        let (mut changes, mut last_id) = match subscribed.await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("Listen error: {e:?}");
+                let _ = tx.send(Err(e)).await;
+                return rx;
+            }
+        };
+
+        tokio::spawn(async move {
+            loop {
+                // notification has no payload, we get all the changes since last time from the queue
+                loop {
+                    let batch = match fetch_changes(&pool, last_id, CHANGES_BATCH_SIZE).await {
+                        Ok(batch) => batch,
+                        Err(e) => {
+                            warn!("Listen error: {e:?}");
+                            let _ = tx.send(Err(e)).await;
+                            return;
+                        }
+                    };
+                    let drained = batch.len() < CHANGES_BATCH_SIZE as usize;
+                    for change in batch {
+                        last_id = change.change_id;
+                        if !request.matches(&change) {
+                            continue;
+                        }
+                        if tx.send(Ok(change)).await.is_err() {
+                            // rx is dropped, so client disconnected
+                            return;
+                        }
+                    }
+                    if drained {
+                        break;
+                    }
+                }
+
+                tokio::select! {
+                    // rx is dropped, so client disconnected
+                    _ = tx.closed() => return,
+                    // lagged only means some wake-ups were merged, the queue still has everything
+                    ret = changes.recv() => {
+                        if let Err(broadcast::error::RecvError::Closed) = ret {
+                            // the shared listener is gone, set up a new one and catch up from the queue
+                            changes = match manager.subscribe().await {
+                                Ok(changes) => changes,
+                                Err(e) => {
+                                    warn!("Listen error: {e:?}");
+                                    let _ = tx.send(Err(e)).await;
+                                    return;
+                                }
+                            };
+                        }
+                    }
+                }
+            }
+        });
+        rx
+    }
 }
 
 impl ReservationManager {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            changes: Default::default(),
+            actor: None,
+        }
+    }
+
+    /// a manager sharing the same pool and change feed, changes made by it are recorded as made
+    /// by the actor
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            changes: self.changes.clone(),
+            actor: Some(actor.into()),
+        }
+    }
+
+    /// begin a transaction for writes, the audit trigger reads the actor from it
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, abi::Error> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(actor) = &self.actor {
+            sqlx::query("SELECT set_config('rsvp.actor', $1, true)")
+                .bind(actor)
+                .execute(&mut tx)
+                .await?;
+        }
+        Ok(tx)
+    }
+
+    async fn set_status(
+        &self,
+        id: ReservationId,
+        status: abi::ReservationStatus,
+        reason: &str,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, abi::Error> {
+        // lock the reservation, so the current status could not change under us
+        id.validate()?;
+        let mut tx = self.begin().await?;
+        let rsvp: abi::Reservation =
+            sqlx::query_as("SELECT * FROM rsvp.reservations WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_one(&mut tx)
+                .await?;
+        rsvp.check_version(expected_version)?;
+        let current = rsvp.status();
+        let status = current.transition(status)?;
+        if current == status {
+            return Ok(rsvp);
+        }
+        let rsvp = sqlx::query_as(
+            "UPDATE rsvp.reservations SET status = $1::rsvp.reservation_status,
+            cancelled_at = CASE WHEN $1 = 'cancelled' THEN now() END,
+            cancel_reason = CASE WHEN $1 = 'cancelled' THEN NULLIF($2, '') END
+            WHERE id = $3 RETURNING *",
+        )
+        .bind(status.to_string())
+        .bind(reason)
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    /// look up the existing reservations blocking the window for a conflict error, the database
+    /// only tells the timespan of one of them. other errors are returned as is
+    async fn with_existing(
+        &self,
+        err: abi::Error,
+        rid: Option<&str>,
+        timespan: PgRange<DateTime<Utc>>,
+        id: Option<ReservationId>,
+    ) -> abi::Error {
+        let info = match err {
+            abi::Error::ConflictReservation { info, existing, .. } if existing.is_empty() => info,
+            err => return err,
+        };
+        let ret = fetch_existing(&self.pool, rid, timespan.clone(), id).await;
+        let existing = match ret {
+            Ok(existing) => existing,
+            Err(e) => {
+                warn!("Failed to look up conflicting reservations: {:?}", e);
+                vec![]
+            }
+        };
+        // the window of the new reservation is known even if the database error is not parsed
+        let info = match (*info, existing.first()) {
+            (abi::ReservationConflictInfo::Unparsed(_), Some(old)) => {
+                abi::ReservationConflictInfo::Parsed(ReservationConflict {
+                    new: ReservationWindow::new(&old.resource_id, timespan),
+                    old: old.get_window(),
+                })
+            }
+            (info, _) => info,
+        };
+        abi::Error::ConflictReservation {
+            info: Box::new(info),
+            existing,
+            suggestions: vec![],
+        }
+    }
+
+    async fn earliest_slot(&self, request: &abi::FindSlotRequest) -> Result<TimeSlot, abi::Error> {
+        let slot: Option<(String, PgRange<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT rid, tstzrange(lower(slot), lower(slot) + $5, '[)') FROM (
+                SELECT unnest($1::text[]) AS rid
+                UNION
+                SELECT resource_id FROM rsvp.reservations WHERE $2 <> '' AND starts_with(resource_id, $2)
+            ) AS candidates, rsvp.availability(rid, $3, $4, $5) AS slot
+            ORDER BY lower(slot), rid LIMIT 1",
+        )
+        .bind(&request.resource_ids)
+        .bind(&request.resource_prefix)
+        .bind(request.earliest_start.clone().map(convert_to_utc_time))
+        .bind(request.latest_end.clone().map(convert_to_utc_time))
+        .bind(request.duration.clone().map(convert_to_interval))
+        .fetch_optional(&self.pool)
+        .await?;
+        let (rid, slot) = slot.ok_or(abi::Error::NoAvailableSlot)?;
+        Ok(TimeSlot::new(rid, slot))
     }
     pub async fn from_config(config: &DbConfig) -> Result<Self, abi::Error> {
         let url = config.url();
@@ -161,6 +782,207 @@ impl ReservationManager {
             .await?;
         Ok(Self::new(pool))
     }
+
+    /// get woken up when reservations change. The first subscriber starts listening to the
+    /// changes channel on a connection shared by all the subscribers of the manager
+    async fn subscribe(&self) -> Result<broadcast::Receiver<()>, abi::Error> {
+        let mut changes = self.changes.lock().await;
+        if let Some(sender) = changes.as_ref() {
+            return Ok(sender.subscribe());
+        }
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(CHANGES_CHANNEL).await?;
+        let (sender, receiver) = broadcast::channel(CHANGES_WAKE_UPS);
+        *changes = Some(sender.clone());
+
+        let shared = self.changes.clone();
+        tokio::spawn(async move {
+            loop {
+                // Ok(None) means the connection was re-established, wake up the subscribers anyway
+                // to get what they missed from the queue
+                let ret = listener.try_recv().await;
+                if let Err(e) = &ret {
+                    warn!("Listen error: {e:?}");
+                }
+                if ret.is_err() || sender.send(()).is_err() {
+                    let mut changes = shared.lock().await;
+                    // someone subscribed after the wake-up was sent to nobody, keep listening
+                    if ret.is_ok() && sender.receiver_count() > 0 {
+                        continue;
+                    }
+                    // subscribers see the channel closed once the sender is gone, and set up a new one
+                    *changes = None;
+                    return;
+                }
+            }
+        });
+        Ok(receiver)
+    }
+}
+
+const CHANGES_CHANNEL: &str = "reservation_update";
+const CHANGES_WAKE_UPS: usize = 16;
+const CHANGES_BATCH_SIZE: i64 = 100;
+
+/// the reservation in the row snapshot `s.snapshot` of the change `c`. Snapshots taken before
+/// created_at and updated_at were added don't have them, the time of the change is used instead.
+/// Those taken before version was added are at version 1, as the rows were when it was added
+const SNAPSHOT_SQL: &str = "jsonb_populate_record(
+    NULL::rsvp.reservations,
+    jsonb_build_object('created_at', c.changed_at, 'updated_at', c.changed_at, 'version', 1)
+        || s.snapshot
+)";
+const FIND_SLOT_ATTEMPTS: usize = 10;
+
+async fn last_change_id(pool: &PgPool) -> Result<i64, abi::Error> {
+    let id =
+        sqlx::query_scalar("SELECT COALESCE(MAX(id), 0)::bigint FROM rsvp.reservation_changes")
+            .fetch_one(pool)
+            .await?;
+    Ok(id)
+}
+
+/// get at most `limit` changes after the given change id, in the order they were made. The
+/// reservation is the one recorded by the change: the row after it, or before it for a delete
+async fn fetch_changes(
+    pool: &PgPool,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<ListenResponse>, abi::Error> {
+    let sql = format!(
+        "SELECT c.id::bigint AS change_id, c.reservation_id, c.op, r.* FROM rsvp.reservation_changes c
+        CROSS JOIN LATERAL (VALUES (COALESCE(c.new, c.old))) AS s(snapshot)
+        CROSS JOIN LATERAL {SNAPSHOT_SQL} AS r
+        WHERE c.id > $1 ORDER BY c.id LIMIT $2"
+    );
+    let rows = sqlx::query(&sql)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    let mut changes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let op = ReservationUpdateType::from(row.get::<RsvpUpdateType, _>("op"));
+        // changes recorded before the row snapshots were kept have none, only id will be populated
+        let rsvp = match row.get::<Option<i64>, _>("id") {
+            Some(_) => Reservation::from_row(&row)?,
+            None => Reservation {
+                id: row.get("reservation_id"),
+                ..Default::default()
+            },
+        };
+        changes.push(ListenResponse {
+            op: op as i32,
+            reservation: Some(rsvp),
+            change_id: row.get("change_id"),
+        });
+    }
+    Ok(changes)
+}
+
+/// check a reservation could be made, and get the status to make it with
+fn new_status(rsvp: &Reservation) -> Result<abi::ReservationStatus, abi::Error> {
+    rsvp.validate()?;
+    let status =
+        abi::ReservationStatus::from_i32(rsvp.status).unwrap_or(abi::ReservationStatus::Pending);
+    if status == abi::ReservationStatus::Cancelled {
+        return Err(abi::Error::InvalidStatus(rsvp.status));
+    }
+    Ok(status)
+}
+
+async fn insert(
+    conn: &mut PgConnection,
+    rsvp: &Reservation,
+    status: abi::ReservationStatus,
+) -> Result<Reservation, sqlx::Error> {
+    sqlx::query_as(
+        "INSERT INTO rsvp.reservations (user_id, resource_id, timespan, note, status) VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status) RETURNING *",
+    )
+    .bind(&rsvp.user_id)
+    .bind(&rsvp.resource_id)
+    .bind(rsvp.get_timespan())
+    .bind(&rsvp.note)
+    .bind(status.to_string())
+    .fetch_one(conn)
+    .await
+}
+
+/// the reservations taking the window of the resource, except the given one. without a
+/// resource, the resource of the given reservation is used
+async fn fetch_existing<'e>(
+    executor: impl PgExecutor<'e>,
+    rid: Option<&str>,
+    timespan: PgRange<DateTime<Utc>>,
+    id: Option<ReservationId>,
+) -> Result<Vec<Reservation>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT * FROM rsvp.reservations
+        WHERE resource_id = COALESCE($1, (SELECT resource_id FROM rsvp.reservations WHERE id = $3))
+        AND timespan && $2 AND status <> 'cancelled' AND id IS DISTINCT FROM $3
+        ORDER BY lower(timespan), id",
+    )
+    .bind(rid)
+    .bind(timespan)
+    .bind(id)
+    .fetch_all(executor)
+    .await
+}
+
+/// the reservations of the query function
+const QUERY_SQL: &str = "SELECT * FROM rsvp.query($1,$2, $3, $4, $5::rsvp.reservation_status[], $6, $7, $8, $9, $10, $11, $12, $13, $14)";
+
+/// the reservations of the query function, locked for a change
+const QUERY_LOCK_SQL: &str = "SELECT * FROM rsvp.reservations WHERE id IN (
+    SELECT id FROM rsvp.query($1,$2, $3, $4, $5::rsvp.reservation_status[], $6, $7, $8, $9, $10, $11, $12, $13, $14)
+) ORDER BY id FOR UPDATE";
+
+/// bind the query to the sql of the query function, limited to the given number of rows
+/// starting after the page cursor
+fn query_sql(
+    sql: &'static str,
+    query: &abi::ReservationQuery,
+    limit: Option<i64>,
+) -> Result<QueryAs<'static, Postgres, Reservation, PgArguments>, abi::Error> {
+    let statuses: Vec<String> = query
+        .get_statuses()?
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    // the cursor only makes sense for paged queries, which are ordered by start time
+    let (after_start, after_id) = match limit {
+        Some(_) => query.get_page_cursor()?.unzip(),
+        None => (None, None),
+    };
+    let sort_by = abi::ReservationSortBy::from_i32(query.sort_by).unwrap_or_default();
+    let match_mode = abi::ReservationMatchMode::from_i32(query.match_mode).unwrap_or_default();
+    let sql = sqlx::query_as(sql)
+        .bind(string_to_option(&query.user_id))
+        .bind(string_to_option(&query.resource_id))
+        .bind(query.start.clone().map(convert_to_utc_time))
+        .bind(query.end.clone().map(convert_to_utc_time))
+        .bind(statuses)
+        .bind(query.desc)
+        .bind(query.include_cancelled)
+        .bind(sort_by.to_string())
+        .bind(query.created.as_ref().map(|r| r.get_range()))
+        .bind(query.updated.as_ref().map(|r| r.get_range()))
+        .bind(match_mode.to_string())
+        .bind(limit)
+        .bind(after_start)
+        .bind(after_id);
+    Ok(sql)
+}
+
+/// estimated number of rows from the top line of a query plan, e.g.
+/// `Seq Scan on reservations  (cost=0.00..1.01 rows=42 width=4)`
+fn plan_rows(plan: &str) -> Option<i64> {
+    let rows = plan.split("rows=").nth(1)?;
+    rows.split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
 }
 
 fn string_to_option(s: &str) -> Option<String> {
@@ -175,8 +997,9 @@ fn string_to_option(s: &str) -> Option<String> {
 mod test {
     use super::*;
     use abi::{
-        Reservation, ReservationConflict, ReservationConflictInfo, ReservationFilterBuilder,
-        ReservationQueryBuilder, ReservationWindow,
+        BatchConflict, CancelRequest, ConfirmRequest, GetRequest, Reservation, ReservationConflict,
+        ReservationConflictInfo, ReservationFilterBuilder, ReservationQueryBuilder,
+        ReservationWindow,
     };
     use prost_types::Timestamp;
 
@@ -188,7 +1011,7 @@ mod test {
     }
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
     async fn reserve_conflict_reservation_should_reject() {
-        let (_rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
         let rsvp2 = abi::Reservation::new_pending(
             "aliceid",
             "ocean-view-room-713",
@@ -212,7 +1035,14 @@ mod test {
             },
         });
 
-        assert_eq!(err, abi::Error::ConflictReservation(info));
+        assert_eq!(
+            err,
+            abi::Error::ConflictReservation {
+                info: Box::new(info),
+                existing: vec![rsvp1],
+                suggestions: vec![]
+            }
+        );
 
         // if let abi::Error::ConflictReservation(ReservationConflictInfo::Parsed(info)) = err {
         //   assert_eq!(info.old.rid, "ocean-view-room-713");
@@ -229,7 +1059,10 @@ mod test {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
         //let id = rsvp.id;
         //assert!(!&rsvp.id.is_empty());
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+        let rsvp = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
         assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
     }
 
@@ -237,11 +1070,60 @@ mod test {
     async fn reserve_change_status_not_pending_should_do_nothing() {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
 
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+        let rsvp = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
+        let changes = count_changes(&migrated_pool).await;
         // change status again should do nothing
-        let ret = manager.change_status(rsvp.id).await.unwrap_err();
-        assert_eq!(ret, abi::Error::NotFound);
+        let ret = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
+        assert_eq!(ret, rsvp);
         assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
+        assert_eq!(count_changes(&migrated_pool).await, changes);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn transition_should_follow_state_machine() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+
+        let rsvp = manager
+            .transition(rsvp.id, abi::ReservationStatus::Blocked)
+            .await
+            .unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::Blocked as i32);
+
+        let err = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition(
+                abi::ReservationStatus::Blocked,
+                abi::ReservationStatus::Confirmed
+            )
+        );
+
+        let rsvp = manager
+            .transition(rsvp.id, abi::ReservationStatus::Pending)
+            .await
+            .unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::Pending as i32);
+
+        let err = manager
+            .transition(rsvp.id, abi::ReservationStatus::Unknown)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidStatus(0));
+
+        let err = manager
+            .transition(rsvp.id + 100, abi::ReservationStatus::Confirmed)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
@@ -257,7 +1139,7 @@ mod test {
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
     async fn get_reservation_should_work() {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
-        let rsvp1 = manager.get(rsvp.id).await.unwrap();
+        let rsvp1 = manager.get(GetRequest::new(rsvp.id)).await.unwrap();
         assert_eq!(rsvp, rsvp1);
     }
 
@@ -265,28 +1147,430 @@ mod test {
     async fn delete_reservation_should_work() {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
         manager.delete(rsvp.id).await.unwrap();
-        let rsvp1 = manager.get(rsvp.id).await.unwrap_err();
+        let rsvp1 = manager.get(GetRequest::new(rsvp.id)).await.unwrap_err();
         assert_eq!(rsvp1, abi::Error::NotFound);
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn query_reservations_should_work() {
+    async fn cancel_reservation_should_keep_history() {
+        let (rsvp, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let cancelled = manager
+            .cancel(CancelRequest::new(rsvp.id, "plan changed"))
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, abi::ReservationStatus::Cancelled as i32);
+        assert_eq!(cancelled.cancel_reason, "plan changed");
+        assert!(cancelled.cancelled_at.is_some());
+
+        // cancel again should do nothing
+        let rsvp1 = manager
+            .cancel(CancelRequest::new(rsvp.id, "again"))
+            .await
+            .unwrap();
+        assert_eq!(rsvp1, cancelled);
+
+        // cancelled reservation is hidden unless asked
+        let err = manager.get(GetRequest::new(rsvp.id)).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+        let request = GetRequest {
+            id: rsvp.id,
+            include_cancelled: true,
+        };
+        assert_eq!(manager.get(request).await.unwrap(), cancelled);
+
+        // cancelled reservation could not be updated
+        let mut changed = cancelled.clone();
+        changed.note = "changed".into();
+        let err = manager
+            .update(abi::UpdateRequest::new(rsvp.id, changed, &["note"]))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+
+        let err = manager
+            .transition(rsvp.id, abi::ReservationStatus::Pending)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition(
+                abi::ReservationStatus::Cancelled,
+                abi::ReservationStatus::Pending
+            )
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn cancelled_reservation_should_not_block_resource() {
+        let (rsvp, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        manager
+            .cancel(CancelRequest::new(rsvp.id, ""))
+            .await
+            .unwrap();
+
+        let rsvp1 = abi::Reservation::new_pending(
+            "aliceid",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        assert!(manager.check(rsvp1.clone()).await.unwrap().is_empty());
+        let rsvp1 = manager.reserve(rsvp1).await.unwrap();
+        assert_ne!(rsvp1.id, rsvp.id);
+
+        let err = manager
+            .reserve(abi::Reservation {
+                status: abi::ReservationStatus::Cancelled as i32,
+                ..rsvp1
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidStatus(4));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_and_filter_should_include_cancelled_if_asked() {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let cancelled = manager
+            .cancel(CancelRequest::new(rsvp.id, ""))
+            .await
+            .unwrap();
+
         let query = ReservationQueryBuilder::default()
             .user_id("aliceid")
-            .start("2023-01-01T15:00:00-0700".parse::<Timestamp>().unwrap())
-            .end("2023-02-28T12:00:00-0700".parse::<Timestamp>().unwrap())
             .status(abi::ReservationStatus::Pending as i32)
             .build()
             .unwrap();
-        // ::new(
-        //   "aliceid",
-        //   "",
-        //   "2021-11-01T15:00:00-0700".parse().unwrap(),
-        //   "2023-12-30T12:00:00-0700".parse().unwrap(),
-        //   ReservationStatus::Pending,
-        //   1,
-        //   10,
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, None);
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .status(abi::ReservationStatus::Pending as i32)
+            .include_cancelled(true)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(cancelled.clone())));
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .include_cancelled(true)
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![cancelled]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_and_filter_should_match_status_set() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rsvps = Vec::new();
+        for (i, rid) in ["room-1", "room-2", "room-3", "room-4"].iter().enumerate() {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                *rid,
+                "2023-01-25T15:00:00-0700".parse().unwrap(),
+                "2023-02-25T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            let rsvp = manager.reserve(rsvp).await.unwrap();
+            let status = match i {
+                0 => abi::ReservationStatus::Pending,
+                1 => abi::ReservationStatus::Confirmed,
+                2 => abi::ReservationStatus::Blocked,
+                _ => abi::ReservationStatus::Cancelled,
+            };
+            rsvps.push(manager.transition(rsvp.id, status).await.unwrap());
+        }
+
+        // unknown status matches any status but cancelled
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .start("2023-01-01T15:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-03-01T12:00:00-0700".parse::<Timestamp>().unwrap())
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        let mut ret = Vec::new();
+        while let Some(r) = rx.recv().await {
+            ret.push(r.unwrap());
+        }
+        assert_eq!(ret, rsvps[..3].to_vec());
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .start("2023-01-01T15:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-03-01T12:00:00-0700".parse::<Timestamp>().unwrap())
+            .statuses(vec![
+                abi::ReservationStatus::Pending as i32,
+                abi::ReservationStatus::Confirmed as i32,
+            ])
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        ret.clear();
+        while let Some(r) = rx.recv().await {
+            ret.push(r.unwrap());
+        }
+        assert_eq!(ret, rsvps[..2].to_vec());
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .build()
+            .unwrap();
+        let (_, ret) = manager.filter(filter).await.unwrap();
+        assert_eq!(ret, rsvps[..3].to_vec());
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .status(abi::ReservationStatus::Blocked as i32)
+            .statuses(vec![abi::ReservationStatus::Cancelled as i32])
+            .build()
+            .unwrap();
+        let (_, ret) = manager.filter(filter).await.unwrap();
+        assert_eq!(ret, rsvps[2..].to_vec());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_should_match_window_by_mode() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rsvps = Vec::new();
+        // before, across the window start, inside, across the window end, after
+        for (start, end) in [
+            ("2023-01-01T10:00:00-0700", "2023-01-02T10:00:00-0700"),
+            ("2023-01-04T10:00:00-0700", "2023-01-06T10:00:00-0700"),
+            ("2023-01-06T10:00:00-0700", "2023-01-07T10:00:00-0700"),
+            ("2023-01-09T10:00:00-0700", "2023-01-11T10:00:00-0700"),
+            ("2023-01-12T10:00:00-0700", "2023-01-13T10:00:00-0700"),
+        ] {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                "ixia-test-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            );
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+
+        for (mode, expected) in [
+            (abi::ReservationMatchMode::Contained, vec![2]),
+            (abi::ReservationMatchMode::Overlapping, vec![1, 2, 3]),
+            (abi::ReservationMatchMode::StartingWithin, vec![2, 3]),
+            (abi::ReservationMatchMode::EndingWithin, vec![1, 2]),
+        ] {
+            let query = ReservationQueryBuilder::default()
+                .resource_id("ixia-test-1")
+                .start("2023-01-05T10:00:00-0700".parse::<Timestamp>().unwrap())
+                .end("2023-01-10T10:00:00-0700".parse::<Timestamp>().unwrap())
+                .match_mode(mode as i32)
+                .build()
+                .unwrap();
+            let mut rx = manager.query(query).await;
+            let mut ret = Vec::new();
+            while let Some(r) = rx.recv().await {
+                ret.push(r.unwrap());
+            }
+            let expected: Vec<_> = expected.into_iter().map(|i| rsvps[i].clone()).collect();
+            assert_eq!(ret, expected, "match mode {}", mode);
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_page_should_continue_from_token() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rsvps = Vec::new();
+        for day in [5, 1, 3, 2, 4] {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                "ixia-test-1",
+                format!("2023-01-0{}T10:00:00-0700", day).parse().unwrap(),
+                format!("2023-01-0{}T12:00:00-0700", day).parse().unwrap(),
+                "",
+            );
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+        rsvps.sort_by_key(|r| r.start.as_ref().unwrap().seconds);
+
+        for desc in [false, true] {
+            let mut expected = rsvps.clone();
+            if desc {
+                expected.reverse();
+            }
+            let mut pages = Vec::new();
+            let mut token = String::new();
+            loop {
+                let query = ReservationQueryBuilder::default()
+                    .resource_id("ixia-test-1")
+                    .desc(desc)
+                    .page_size(2)
+                    .page_token(token)
+                    .build()
+                    .unwrap();
+                let (next, page) = manager.query_page(query).await.unwrap();
+                pages.push(page);
+                match next {
+                    Some(next) => token = next,
+                    None => break,
+                }
+            }
+            assert_eq!(
+                pages,
+                vec![
+                    expected[..2].to_vec(),
+                    expected[2..4].to_vec(),
+                    expected[4..].to_vec()
+                ]
+            );
+        }
+
+        let query = ReservationQueryBuilder::default()
+            .page_size(2)
+            .page_token("not a token")
+            .build()
+            .unwrap();
+        let err = manager.query_page(query).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidPageToken("not a token".into()));
+
+        // paged queries are always ordered by start time
+        let query = ReservationQueryBuilder::default()
+            .page_size(2)
+            .sort_by(abi::ReservationSortBy::Id as i32)
+            .build()
+            .unwrap();
+        let err = manager.query_page(query).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidSortBy(abi::ReservationSortBy::Id as i32)
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn history_should_record_every_change() {
+        let manager = ReservationManager::new(migrated_pool.clone()).with_actor("alice-admin");
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "ixia-test-1",
+            "2023-01-25T15:00:00-0700".parse().unwrap(),
+            "2023-02-25T12:00:00-0700".parse().unwrap(),
+            "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
+        );
+        let rsvp = manager.reserve(rsvp).await.unwrap();
+        let rsvp = manager.get(GetRequest::new(rsvp.id)).await.unwrap();
+        let noted = manager
+            .update_note(rsvp.id, "hello world".into())
+            .await
+            .unwrap();
+        // changes without actor are recorded as well
+        let cancelled = ReservationManager::new(migrated_pool.clone())
+            .cancel(CancelRequest::new(rsvp.id, "plan changed"))
+            .await
+            .unwrap();
+        manager.delete(rsvp.id).await.unwrap();
+
+        let changes = manager.history(rsvp.id).await.unwrap();
+        assert_eq!(changes.len(), 4);
+        let ops: Vec<_> = changes.iter().map(|c| c.op()).collect();
+        assert_eq!(
+            ops,
+            vec![
+                ReservationUpdateType::Create,
+                ReservationUpdateType::Update,
+                ReservationUpdateType::Update,
+                ReservationUpdateType::Delete,
+            ]
+        );
+        let actors: Vec<_> = changes.iter().map(|c| c.actor.as_str()).collect();
+        assert_eq!(
+            actors,
+            vec!["alice-admin", "alice-admin", "", "alice-admin"]
+        );
+        assert!(changes.iter().all(|c| c.changed_at.is_some()));
+        assert!(changes.windows(2).all(|w| w[0].id < w[1].id));
+
+        assert_eq!(changes[0].old, None);
+        assert_eq!(changes[0].new, Some(rsvp.clone()));
+        assert_eq!(changes[1].old, Some(rsvp.clone()));
+        assert_eq!(changes[1].new, Some(noted.clone()));
+        assert_eq!(changes[2].old, Some(noted));
+        assert_eq!(changes[2].new, Some(cancelled.clone()));
+        assert_eq!(changes[3].old, Some(cancelled));
+        assert_eq!(changes[3].new, None);
+
+        let err = manager.history(rsvp.id + 100).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn history_should_decode_snapshots_without_version() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let rsvp = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
+        // snapshots recorded before the version was added
+        sqlx::query(
+            "UPDATE rsvp.reservation_changes SET old = old - 'version', new = new - 'version' WHERE reservation_id = $1",
+        )
+        .bind(rsvp.id)
+        .execute(&migrated_pool)
+        .await
+        .unwrap();
+
+        let changes = manager.history(rsvp.id).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].old.as_ref().unwrap().version, 1);
+        assert_eq!(changes[1].new.as_ref().unwrap().version, 1);
+        assert_eq!(changes[1].new.as_ref().unwrap().status, rsvp.status);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn history_should_decode_snapshots_without_timestamps() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        // snapshots recorded before the timestamps were added
+        sqlx::query(
+            "UPDATE rsvp.reservation_changes SET new = new - 'created_at' - 'updated_at' WHERE reservation_id = $1",
+        )
+        .bind(rsvp.id)
+        .execute(&migrated_pool)
+        .await
+        .unwrap();
+
+        let changes = manager.history(rsvp.id).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        let new = changes[0].new.clone().unwrap();
+        assert_eq!(new.created_at, changes[0].changed_at);
+        assert_eq!(new.updated_at, changes[0].changed_at);
+        assert_eq!(new.note, rsvp.note);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_work() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .start("2023-01-01T15:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-02-28T12:00:00-0700".parse::<Timestamp>().unwrap())
+            .status(abi::ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        // ::new(
+        //   "aliceid",
+        //   "",
+        //   "2021-11-01T15:00:00-0700".parse().unwrap(),
+        //   "2023-12-30T12:00:00-0700".parse().unwrap(),
+        //   ReservationStatus::Pending,
+        //   1,
+        //   10,
         //   false
         // );
         let mut rx = manager.query(query).await;
@@ -317,12 +1601,87 @@ mod test {
         // if timespan is empty, should return empty
 
         // change status to confirmed, query should get result
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+        let rsvp = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
         let mut rx = manager.query(query.clone()).await;
         assert_eq!(rx.recv().await, Some(Ok(rsvp)));
         //assert_eq!(rx.recv().await, None);
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reservation_timestamps_should_be_maintained() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        assert!(rsvp.created_at.is_some());
+        assert_eq!(rsvp.created_at, rsvp.updated_at);
+
+        let rsvp1 = manager
+            .update_note(rsvp.id, "hello world".into())
+            .await
+            .unwrap();
+        assert_eq!(rsvp1.created_at, rsvp.created_at);
+        assert!(updated_at(&rsvp1) > updated_at(&rsvp));
+
+        // writing the same values doesn't count as a change
+        let rsvp2 = manager
+            .update_note(rsvp.id, "hello world".into())
+            .await
+            .unwrap();
+        assert_eq!(rsvp2, rsvp1);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_and_filter_should_sort_and_filter_by_timestamps() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        // alice's reservation is made later but starts later too, touch tyr's to reverse the update order
+        let rsvp1 = manager
+            .update_note(rsvp1.id, "updated".into())
+            .await
+            .unwrap();
+
+        let query = ReservationQueryBuilder::default()
+            .status(abi::ReservationStatus::Pending as i32)
+            .sort_by(abi::ReservationSortBy::UpdatedAt as i32)
+            .desc(true)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp1.clone())));
+        assert_eq!(rx.recv().await, Some(Ok(rsvp2.clone())));
+        assert_eq!(rx.recv().await, None);
+
+        let query = ReservationQueryBuilder::default()
+            .status(abi::ReservationStatus::Pending as i32)
+            .updated(abi::TimeRange {
+                start: rsvp1.updated_at.clone(),
+                end: None,
+            })
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp1.clone())));
+        assert_eq!(rx.recv().await, None);
+
+        let filter = ReservationFilterBuilder::default()
+            .sort_by(abi::ReservationSortBy::UpdatedAt as i32)
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp2.clone(), rsvp1.clone()]);
+
+        let filter = ReservationFilterBuilder::default()
+            .created(abi::TimeRange {
+                start: rsvp2.created_at.clone(),
+                end: None,
+            })
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp2]);
+    }
+
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
     async fn filter_reservations_should_work() {
         let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
@@ -338,7 +1697,1128 @@ mod test {
         assert_eq!(rsvps[0], rsvp);
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_should_count_total_if_asked() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        for i in 0..12 {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                format!("router-{}", i),
+                "2023-01-25T15:00:00-0700".parse().unwrap(),
+                "2023-02-25T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            manager.reserve(rsvp).await.unwrap();
+        }
+        sqlx::query("ANALYZE rsvp.reservations")
+            .execute(&migrated_pool)
+            .await
+            .unwrap();
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps.len(), 10);
+        assert_eq!(pager.total, None);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .total_mode(abi::ReservationTotalMode::Exact as i32)
+            .build()
+            .unwrap();
+        let (pager, _) = manager.filter(filter).await.unwrap();
+        assert_eq!(pager.total, Some(12));
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .total_mode(abi::ReservationTotalMode::Estimate as i32)
+            .build()
+            .unwrap();
+        let (pager, _) = manager.filter(filter).await.unwrap();
+        assert_eq!(pager.total, Some(12));
+    }
+
+    #[test]
+    fn plan_rows_should_parse_top_line() {
+        assert_eq!(
+            plan_rows("Seq Scan on reservations  (cost=0.00..1.01 rows=42 width=4)"),
+            Some(42)
+        );
+        assert_eq!(plan_rows("Result"), None);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_should_page_by_every_sort_key() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rsvps = Vec::new();
+        for i in 0..25 {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                format!("router-{}", i),
+                format!("2023-02-{:02}T10:00:00-0700", (i * 7) % 25 + 1)
+                    .parse()
+                    .unwrap(),
+                format!("2023-02-{:02}T{}:00:00-0700", (i * 7) % 25 + 1, 11 + i % 3)
+                    .parse()
+                    .unwrap(),
+                "",
+            );
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+
+        let key = |sort_by, r: &Reservation| {
+            let ts = match sort_by {
+                abi::ReservationSortBy::Start => r.start.clone(),
+                abi::ReservationSortBy::End => r.end.clone(),
+                abi::ReservationSortBy::CreatedAt => r.created_at.clone(),
+                _ => None,
+            }
+            .unwrap_or_default();
+            (ts.seconds, ts.nanos, r.id)
+        };
+        for sort_by in [
+            abi::ReservationSortBy::Id,
+            abi::ReservationSortBy::Start,
+            abi::ReservationSortBy::End,
+            abi::ReservationSortBy::CreatedAt,
+        ] {
+            for desc in [false, true] {
+                let mut expected = rsvps.clone();
+                expected.sort_by_key(|r| key(sort_by, r));
+                if desc {
+                    expected.reverse();
+                }
+                let filter = |cursor: &str| {
+                    ReservationFilterBuilder::default()
+                        .user_id("aliceid")
+                        .sort_by(sort_by as i32)
+                        .desc(desc)
+                        .page_cursor(cursor)
+                        .build()
+                        .unwrap()
+                };
+
+                let mut pages = Vec::new();
+                let mut cursor = String::new();
+                loop {
+                    let (pager, page) = manager.filter(filter(&cursor)).await.unwrap();
+                    assert_eq!(pager.prev_cursor.is_empty(), pages.is_empty());
+                    pages.push((pager.prev_cursor, page));
+                    if pager.next_cursor.is_empty() {
+                        break;
+                    }
+                    cursor = pager.next_cursor;
+                }
+                let ret: Vec<_> = pages.iter().flat_map(|(_, p)| p.clone()).collect();
+                assert_eq!(pages.len(), 3);
+                assert_eq!(ret, expected, "sort by {} desc {}", sort_by, desc);
+
+                // going back from the last page gives the pages before it
+                let (pager, page) = manager.filter(filter(&pages[2].0)).await.unwrap();
+                assert_eq!(page, pages[1].1);
+                assert!(!pager.next_cursor.is_empty());
+                let (pager, page) = manager.filter(filter(&pager.prev_cursor)).await.unwrap();
+                assert_eq!(page, pages[0].1);
+                assert!(pager.prev_cursor.is_empty());
+            }
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_should_match_window_and_note() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rsvps = Vec::new();
+        for (start, end, note) in [
+            (
+                "2023-01-02T10:00:00-0700",
+                "2023-01-02T12:00:00-0700",
+                "Demo for ACME",
+            ),
+            (
+                "2023-01-03T10:00:00-0700",
+                "2023-01-03T12:00:00-0700",
+                "internal sync",
+            ),
+            (
+                "2023-01-05T10:00:00-0700",
+                "2023-01-09T12:00:00-0700",
+                "demo rehearsal",
+            ),
+            (
+                "2023-01-10T10:00:00-0700",
+                "2023-01-10T12:00:00-0700",
+                "100% demo_day",
+            ),
+        ] {
+            let rsvp = abi::Reservation::new_pending(
+                "aliceid",
+                "ixia-test-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                note,
+            );
+            let rsvp = manager.reserve(rsvp).await.unwrap();
+            rsvps.push(
+                manager
+                    .change_status(ConfirmRequest::new(rsvp.id))
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let filter = |mode: abi::ReservationMatchMode, note: &str| {
+            ReservationFilterBuilder::default()
+                .resource_id("ixia-test-1")
+                .status(abi::ReservationStatus::Confirmed as i32)
+                .start("2023-01-02T00:00:00-0700".parse::<Timestamp>().unwrap())
+                .end("2023-01-09T00:00:00-0700".parse::<Timestamp>().unwrap())
+                .match_mode(mode as i32)
+                .note(note)
+                .build()
+                .unwrap()
+        };
+        let (_, ret) = manager
+            .filter(filter(abi::ReservationMatchMode::Contained, "demo"))
+            .await
+            .unwrap();
+        assert_eq!(ret, vec![rsvps[0].clone()]);
+
+        let (_, ret) = manager
+            .filter(filter(abi::ReservationMatchMode::Overlapping, "DEMO"))
+            .await
+            .unwrap();
+        assert_eq!(ret, vec![rsvps[0].clone(), rsvps[2].clone()]);
+
+        let (_, ret) = manager
+            .filter(filter(abi::ReservationMatchMode::Overlapping, ""))
+            .await
+            .unwrap();
+        assert_eq!(ret, rsvps[..3].to_vec());
+
+        // wildcards in the note are matched literally
+        let filter = ReservationFilterBuilder::default()
+            .note("% demo_")
+            .build()
+            .unwrap();
+        let (_, ret) = manager.filter(filter).await.unwrap();
+        assert_eq!(ret, vec![rsvps[3].clone()]);
+        let filter = ReservationFilterBuilder::default()
+            .note("l_s")
+            .build()
+            .unwrap();
+        let (_, ret) = manager.filter(filter).await.unwrap();
+        assert!(ret.is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_and_filter_should_bind_hostile_ids() {
+        let hostile = "alice' OR '1'='1";
+        let (rsvp, manager) = make_reservation(
+            migrated_pool.clone(),
+            hostile,
+            "room'); DROP TABLE rsvp.reservations; --",
+            "2023-01-25T15:00:00-0700".parse().unwrap(),
+            "2023-02-25T12:00:00-0700".parse().unwrap(),
+            "",
+        )
+        .await;
+        make_alice_reservation(migrated_pool.clone()).await;
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id(hostile)
+            .resource_id(rsvp.resource_id.clone())
+            .total_mode(abi::ReservationTotalMode::Exact as i32)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp.clone()]);
+        assert_eq!(pager.total, Some(1));
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("nobody' OR 'a'='a")
+            .total_mode(abi::ReservationTotalMode::Estimate as i32)
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+
+        let query = ReservationQueryBuilder::default()
+            .user_id(hostile)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp.clone())));
+        assert_eq!(rx.recv().await, None);
+
+        let query = ReservationQueryBuilder::default()
+            .resource_id("nobody' OR 'a'='a")
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, None);
+
+        // the plpgsql filter binds the ids as well
+        let rsvps: Vec<Reservation> = sqlx::query_as("SELECT * FROM rsvp.filter($1, $2)")
+            .bind(hostile)
+            .bind(&rsvp.resource_id)
+            .fetch_all(&migrated_pool)
+            .await
+            .unwrap();
+        assert_eq!(rsvps, vec![rsvp]);
+        let rsvps: Vec<Reservation> = sqlx::query_as("SELECT * FROM rsvp.filter($1, NULL)")
+            .bind("nobody' OR 'a'='a")
+            .fetch_all(&migrated_pool)
+            .await
+            .unwrap();
+        assert!(rsvps.is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_reservation_changes_should_work() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let mut rx = manager.listen(ListenRequest::default()).await;
+
+        let (rsvp, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        assert_eq!(change.reservation, Some(rsvp.clone()));
+
+        let rsvp = manager
+            .change_status(ConfirmRequest::new(rsvp.id))
+            .await
+            .unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(rsvp.clone()));
+
+        manager.delete(rsvp.id).await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Delete as i32);
+        assert_eq!(change.reservation, Some(rsvp));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_should_replay_changes_since_given_id() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+
+        // replay everything
+        let mut rx = manager.listen(ListenRequest::new(0)).await;
+        let change1 = rx.recv().await.unwrap().unwrap();
+        let change2 = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change1.reservation, Some(rsvp1));
+        assert_eq!(change2.reservation, Some(rsvp2.clone()));
+        assert!(change1.change_id < change2.change_id);
+
+        // resume after the first change, then get the live changes
+        let mut rx = manager.listen(ListenRequest::new(change1.change_id)).await;
+        assert_eq!(rx.recv().await, Some(Ok(change2.clone())));
+        let rsvp2 = manager
+            .change_status(ConfirmRequest::new(rsvp2.id))
+            .await
+            .unwrap();
+        let change3 = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change3.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change3.reservation, Some(rsvp2));
+        assert!(change3.change_id > change2.change_id);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_should_replay_reservations_as_they_were_changed() {
+        let (pending, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let confirmed = manager
+            .change_status(ConfirmRequest::new(pending.id))
+            .await
+            .unwrap();
+        manager.delete(pending.id).await.unwrap();
+        // more changes than a batch, so replay has to page through them
+        for i in 0..CHANGES_BATCH_SIZE {
+            make_reservation(
+                migrated_pool.clone(),
+                "tyrid",
+                &format!("room-{i}"),
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "batch",
+            )
+            .await;
+        }
+
+        let mut rx = manager.listen(ListenRequest::new(0)).await;
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(pending));
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(confirmed.clone()));
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Delete as i32);
+        assert_eq!(change.reservation, Some(confirmed));
+        for i in 0..CHANGES_BATCH_SIZE {
+            let change = rx.recv().await.unwrap().unwrap();
+            assert_eq!(change.reservation.unwrap().resource_id, format!("room-{i}"));
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_streams_should_share_one_connection() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        // more streams than the 10 connections of a default pool
+        let mut streams = vec![];
+        for _ in 0..12 {
+            streams.push(manager.listen(ListenRequest::default()).await);
+        }
+
+        let (rsvp, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        for rx in streams.iter_mut() {
+            let change = rx.recv().await.unwrap().unwrap();
+            assert_eq!(change.reservation, Some(rsvp.clone()));
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_should_only_send_matched_changes() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let request = ListenRequest {
+            resource_prefix: "ixia-".into(),
+            ops: vec![ReservationUpdateType::Update as i32],
+            ..Default::default()
+        };
+        let mut rx = manager.listen(request).await;
+
+        let (rsvp1, _manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        manager
+            .change_status(ConfirmRequest::new(rsvp1.id))
+            .await
+            .unwrap();
+        let rsvp2 = manager
+            .change_status(ConfirmRequest::new(rsvp2.id))
+            .await
+            .unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(rsvp2));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_should_filter_deleted_reservations() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let request = ListenRequest {
+            user_id: "aliceid".into(),
+            ops: vec![ReservationUpdateType::Delete as i32],
+            ..Default::default()
+        };
+        let mut rx = manager.listen(request).await;
+
+        let (rsvp1, _manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        manager.delete(rsvp1.id).await.unwrap();
+        manager.delete(rsvp2.id).await.unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Delete as i32);
+        assert_eq!(change.reservation, Some(rsvp2));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn listen_with_invalid_filter_should_reject() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let request = ListenRequest {
+            statuses: vec![100],
+            ..Default::default()
+        };
+        let mut rx = manager.listen(request).await;
+        assert_eq!(rx.recv().await, Some(Err(abi::Error::InvalidStatus(100))));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn availability_should_return_free_slots() {
+        let (_rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let mut request = abi::AvailabilityRequest {
+            resource_id: "ixia-test-1".into(),
+            start: "2023-01-01T00:00:00-0700".parse().ok(),
+            end: "2023-03-01T00:00:00-0700".parse().ok(),
+            min_duration: None,
+        };
+        let slots = manager.availability(request.clone()).await.unwrap();
+        assert_eq!(
+            slots,
+            vec![
+                TimeSlot {
+                    resource_id: "ixia-test-1".into(),
+                    start: "2023-01-01T00:00:00-0700".parse().ok(),
+                    end: "2023-01-25T15:00:00-0700".parse().ok(),
+                },
+                TimeSlot {
+                    resource_id: "ixia-test-1".into(),
+                    start: "2023-02-25T12:00:00-0700".parse().ok(),
+                    end: "2023-03-01T00:00:00-0700".parse().ok(),
+                },
+            ]
+        );
+
+        // the second slot is less than 10 days
+        request.min_duration = Some(prost_types::Duration {
+            seconds: 10 * 24 * 3600,
+            nanos: 0,
+        });
+        let slots = manager.availability(request.clone()).await.unwrap();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].end, "2023-01-25T15:00:00-0700".parse().ok());
+
+        // other resources are free in the whole window
+        request.resource_id = "ixia-test-2".into();
+        let slots = manager.availability(request.clone()).await.unwrap();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].start, request.start);
+        assert_eq!(slots[0].end, request.end);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn find_slot_should_return_earliest_slot() {
+        let (_rsvp, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let mut request = abi::FindSlotRequest {
+            resource_ids: vec!["ocean-view-room-713".into()],
+            duration: Some(prost_types::Duration {
+                seconds: 24 * 3600,
+                nanos: 0,
+            }),
+            earliest_start: "2022-12-26T15:00:00-0700".parse().ok(),
+            ..Default::default()
+        };
+        let (slot, rsvp) = manager.find_slot(request.clone()).await.unwrap();
+        assert_eq!(slot.resource_id, "ocean-view-room-713");
+        assert_eq!(slot.start, "2022-12-28T12:00:00-0700".parse().ok());
+        assert_eq!(slot.end, "2022-12-29T12:00:00-0700".parse().ok());
+        assert!(rsvp.is_none());
+
+        // room 714 is free, so it is the earliest
+        request.resource_ids.push("ocean-view-room-714".into());
+        let (slot, _) = manager.find_slot(request.clone()).await.unwrap();
+        assert_eq!(slot.resource_id, "ocean-view-room-714");
+        assert_eq!(slot.start, request.earliest_start);
+
+        // only reserved resources are known by prefix
+        request.resource_ids = vec![];
+        request.resource_prefix = "ocean-view-".into();
+        let (slot, _) = manager.find_slot(request.clone()).await.unwrap();
+        assert_eq!(slot.resource_id, "ocean-view-room-713");
+
+        // no slot if it should end before the reservation ends
+        request.latest_end = "2022-12-28T12:00:00-0700".parse().ok();
+        let err = manager.find_slot(request.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::NoAvailableSlot);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn find_slot_should_reserve_slot_if_asked() {
+        let (_rsvp, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let request = abi::FindSlotRequest {
+            resource_prefix: "ocean-view-".into(),
+            duration: Some(prost_types::Duration {
+                seconds: 24 * 3600,
+                nanos: 0,
+            }),
+            earliest_start: "2022-12-26T15:00:00-0700".parse().ok(),
+            reserve: true,
+            user_id: "aliceid".into(),
+            ..Default::default()
+        };
+        let (slot1, rsvp) = manager.find_slot(request.clone()).await.unwrap();
+        let rsvp = rsvp.unwrap();
+        assert_eq!(rsvp.user_id, "aliceid");
+        assert_eq!(rsvp.resource_id, slot1.resource_id);
+        assert_eq!(rsvp.start, slot1.start);
+        assert_eq!(rsvp.end, slot1.end);
+
+        // the slot is taken, so we get the next one
+        let (slot2, _) = manager.find_slot(request).await.unwrap();
+        assert_eq!(slot2.start, slot1.end);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_many_should_make_all_or_none() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let new = |uid: &str, rid: &str, start: &str, end: &str| {
+            abi::Reservation::new_pending(
+                uid,
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let room = new(
+            "aliceid",
+            "meeting-room-1",
+            "2022-12-26T15:00:00-0700",
+            "2022-12-26T17:00:00-0700",
+        );
+        let projector = new(
+            "aliceid",
+            "projector-1",
+            "2022-12-26T15:00:00-0700",
+            "2022-12-26T17:00:00-0700",
+        );
+        let taken = new(
+            "aliceid",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700",
+            "2022-12-26T17:00:00-0700",
+        );
+        let same_room = new(
+            "aliceid",
+            "meeting-room-1",
+            "2022-12-26T16:00:00-0700",
+            "2022-12-26T18:00:00-0700",
+        );
+
+        // every conflict is reported, including the ones within the batch
+        let err = manager
+            .reserve_many(vec![
+                room.clone(),
+                taken.clone(),
+                projector.clone(),
+                same_room.clone(),
+            ])
+            .await
+            .unwrap_err();
+        let expected = vec![
+            BatchConflict {
+                index: 1,
+                new: taken.get_window(),
+                existing: vec![rsvp1.clone()],
+            },
+            BatchConflict {
+                index: 3,
+                new: same_room.get_window(),
+                existing: vec![room.clone()],
+            },
+        ];
+        assert_eq!(err, abi::Error::ConflictReservations(expected));
+
+        // a conflict before the blocking reservation doesn't shift which one is reported
+        let err = manager
+            .reserve_many(vec![taken.clone(), room.clone(), same_room.clone()])
+            .await
+            .unwrap_err();
+        let expected = vec![
+            BatchConflict {
+                index: 0,
+                new: taken.get_window(),
+                existing: vec![rsvp1.clone()],
+            },
+            BatchConflict {
+                index: 2,
+                new: same_room.get_window(),
+                existing: vec![room.clone()],
+            },
+        ];
+        assert_eq!(err, abi::Error::ConflictReservations(expected));
+
+        // and nothing is made
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query.clone()).await;
+        assert!(rx.recv().await.is_none());
+
+        let rsvps = manager
+            .reserve_many(vec![room.clone(), projector.clone()])
+            .await
+            .unwrap();
+        assert_eq!(rsvps.len(), 2);
+        assert_eq!(rsvps[0].resource_id, "meeting-room-1");
+        assert_eq!(rsvps[1].resource_id, "projector-1");
+        assert!(rsvps[0].id > 0 && rsvps[1].id > 0);
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await.unwrap().unwrap(), rsvps[0]);
+
+        // invalid reservation rejects the whole batch before anything is made
+        let err = manager
+            .reserve_many(vec![
+                same_room,
+                new(
+                    "",
+                    "projector-2",
+                    "2022-12-26T15:00:00-0700",
+                    "2022-12-26T17:00:00-0700",
+                ),
+            ])
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUserId("".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_conflict_should_suggest_nearest_slots_if_asked() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "ocean-view-room-713",
+            "2022-12-26T22:00:00Z".parse().unwrap(),
+            "2022-12-27T22:00:00Z".parse().unwrap(),
+            "",
+        );
+
+        // no suggestion unless asked
+        let err = manager
+            .reserve_or_suggest(abi::ReserveRequest::new(rsvp.clone()))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, abi::Error::ConflictReservation { existing, suggestions, .. } if existing == vec![rsvp1.clone()] && suggestions.is_empty())
+        );
+
+        let err = manager
+            .reserve_or_suggest(abi::ReserveRequest::with_suggestions(rsvp.clone(), 3))
+            .await
+            .unwrap_err();
+        let suggestions = match err {
+            abi::Error::ConflictReservation { suggestions, .. } => suggestions,
+            e => panic!("expect conflict reservation error, got {:?}", e),
+        };
+        // same duration right after and right before the existing one, nearest first
+        let expected: Vec<_> = [
+            ("2022-12-28T19:00:00Z", "2022-12-29T19:00:00Z"),
+            ("2022-12-24T22:00:00Z", "2022-12-25T22:00:00Z"),
+        ]
+        .iter()
+        .map(|(start, end)| abi::TimeSlot {
+            resource_id: "ocean-view-room-713".into(),
+            start: start.parse().ok(),
+            end: end.parse().ok(),
+        })
+        .collect();
+        assert_eq!(suggestions, expected);
+
+        // the suggested slot could be reserved
+        let mut rsvp2 = rsvp.clone();
+        rsvp2.start = expected[0].start.clone();
+        rsvp2.end = expected[0].end.clone();
+        let rsvp2 = manager
+            .reserve_or_suggest(abi::ReserveRequest::with_suggestions(rsvp2, 3))
+            .await
+            .unwrap();
+        assert!(rsvp2.id > rsvp1.id);
+
+        let err = manager
+            .reserve_or_suggest(abi::ReserveRequest::with_suggestions(rsvp, 11))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidSuggestionCount(11));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn bulk_should_change_selected_reservations() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let new = |uid: &str, rid: &str, start: &str, end: &str| {
+            abi::Reservation::new_pending(
+                uid,
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let rsvps = manager
+            .reserve_many(vec![
+                new(
+                    "alice",
+                    "ixia-3230",
+                    "2023-01-01T15:00:00-0700",
+                    "2023-01-02T12:00:00-0700",
+                ),
+                new(
+                    "bob",
+                    "ixia-3230",
+                    "2023-01-03T15:00:00-0700",
+                    "2023-01-04T12:00:00-0700",
+                ),
+                new(
+                    "alice",
+                    "ixia-3231",
+                    "2023-01-01T15:00:00-0700",
+                    "2023-01-02T12:00:00-0700",
+                ),
+            ])
+            .await
+            .unwrap();
+        let ids = |rsvps: &[Reservation]| rsvps.iter().map(|r| r.id).collect::<Vec<_>>();
+        let by_resource = ReservationFilterBuilder::default()
+            .resource_id("ixia-3230")
+            .build()
+            .unwrap();
+        let by_user = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .build()
+            .unwrap();
+
+        // a dry run shows the changes without making them
+        let mut request = abi::BulkRequest::new(by_resource.clone(), abi::BulkAction::Cancel);
+        request.note = "maintenance".into();
+        request.dry_run = true;
+        let changed = manager.bulk(request.clone()).await.unwrap();
+        assert_eq!(ids(&changed), vec![rsvps[0].id, rsvps[1].id]);
+        assert!(changed
+            .iter()
+            .all(|r| r.status() == abi::ReservationStatus::Cancelled
+                && r.cancel_reason == "maintenance"));
+        let rsvp = manager.get(GetRequest::new(rsvps[0].id)).await.unwrap();
+        assert_eq!(rsvp, rsvps[0]);
+
+        // reservations already in the target state are skipped
+        let request = abi::BulkRequest::new(by_user.clone(), abi::BulkAction::Confirm);
+        let changed = manager.bulk(request.clone()).await.unwrap();
+        assert_eq!(ids(&changed), vec![rsvps[0].id, rsvps[2].id]);
+        assert!(changed
+            .iter()
+            .all(|r| r.status() == abi::ReservationStatus::Confirmed));
+        assert!(manager.bulk(request).await.unwrap().is_empty());
+
+        let mut request = abi::BulkRequest::new(by_user, abi::BulkAction::Note);
+        request.note = "demo day".into();
+        let changed = manager.bulk(request).await.unwrap();
+        assert_eq!(ids(&changed), vec![rsvps[0].id, rsvps[2].id]);
+        assert!(changed.iter().all(|r| r.note == "demo day"));
+
+        let mut request = abi::BulkRequest::new(by_resource, abi::BulkAction::Cancel);
+        request.note = "maintenance".into();
+        let changed = manager.bulk(request).await.unwrap();
+        assert_eq!(ids(&changed), vec![rsvps[0].id, rsvps[1].id]);
+        let rsvp = manager
+            .get(GetRequest {
+                id: rsvps[1].id,
+                include_cancelled: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(rsvp.status(), abi::ReservationStatus::Cancelled);
+        assert_eq!(rsvp.cancel_reason, "maintenance");
+
+        // one reservation not able to change fails them all
+        let with_cancelled = ReservationFilterBuilder::default()
+            .resource_id("ixia-3230")
+            .include_cancelled(true)
+            .build()
+            .unwrap();
+        let request = abi::BulkRequest::new(with_cancelled, abi::BulkAction::Confirm);
+        let err = manager.bulk(request).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition(
+                abi::ReservationStatus::Cancelled,
+                abi::ReservationStatus::Confirmed
+            )
+        );
+        let request = abi::BulkRequest {
+            selector: None,
+            ..abi::BulkRequest::new(abi::ReservationFilter::default(), abi::BulkAction::Confirm)
+        };
+        let err = manager.bulk(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidSelector);
+
+        // an empty selector is rejected instead of changing every reservation
+        let request =
+            abi::BulkRequest::new(abi::ReservationQuery::default(), abi::BulkAction::Confirm);
+        let err = manager.bulk(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidSelector);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn check_should_return_conflicts_without_reserving() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_reservation(
+            migrated_pool.clone(),
+            "tyrid",
+            "ocean-view-room-713",
+            "2022-12-29T15:00:00-0700".parse().unwrap(),
+            "2022-12-31T12:00:00-0700".parse().unwrap(),
+            "",
+        )
+        .await;
+        let changes = count_changes(&migrated_pool).await;
+
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        let conflicts = manager.check(rsvp.clone()).await.unwrap();
+        assert_eq!(
+            conflicts,
+            vec![
+                ReservationConflict {
+                    new: rsvp.get_window(),
+                    old: rsvp1.get_window(),
+                },
+                ReservationConflict {
+                    new: rsvp.get_window(),
+                    old: rsvp2.get_window(),
+                },
+            ]
+        );
+
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "ocean-view-room-714",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        assert_eq!(manager.check(rsvp).await.unwrap(), vec![]);
+
+        // nothing is written
+        assert_eq!(count_changes(&migrated_pool).await, changes);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn update_should_only_change_masked_fields() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let mut new = abi::Reservation::new_pending(
+            "bobid",
+            "ixia-test-2",
+            "2023-03-01T15:00:00-0700".parse().unwrap(),
+            "2023-03-05T12:00:00-0700".parse().unwrap(),
+            "hello world",
+        );
+        new.status = abi::ReservationStatus::Confirmed as i32;
+
+        let request = abi::UpdateRequest::new(rsvp.id, new.clone(), &["note", "status"]);
+        let rsvp1 = manager.update(request).await.unwrap();
+        assert_eq!(rsvp1.note, "hello world");
+        assert_eq!(rsvp1.status, abi::ReservationStatus::Confirmed as i32);
+        assert_eq!(rsvp1.user_id, rsvp.user_id);
+        assert_eq!(rsvp1.resource_id, rsvp.resource_id);
+        assert_eq!(rsvp1.start, rsvp.start);
+        assert_eq!(rsvp1.end, rsvp.end);
+
+        let request = abi::UpdateRequest::new(
+            rsvp.id,
+            new.clone(),
+            &["start", "end", "resource_id", "user_id"],
+        );
+        let rsvp2 = manager.update(request).await.unwrap();
+        assert_eq!(rsvp2.id, rsvp.id);
+        assert_eq!(rsvp2.user_id, "bobid");
+        assert_eq!(rsvp2.resource_id, "ixia-test-2");
+        assert_eq!(rsvp2.start, new.start);
+        assert_eq!(rsvp2.end, new.end);
+        assert_eq!(manager.get(GetRequest::new(rsvp.id)).await.unwrap(), rsvp2);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn update_to_taken_window_should_reject() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let new = abi::Reservation::new_pending(
+            "aliceid",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let request =
+            abi::UpdateRequest::new(rsvp2.id, new, &["start", "end", "resource_id", "note"]);
+        let err = manager.update(request).await.unwrap_err();
+        assert!(
+            matches!(err, abi::Error::ConflictReservation { existing, .. } if existing == vec![rsvp1])
+        );
+        assert_eq!(manager.get(GetRequest::new(rsvp2.id)).await.unwrap(), rsvp2);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn update_with_invalid_request_should_reject() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let request = abi::UpdateRequest::new(rsvp.id, rsvp.clone(), &["id"]);
+        let err = manager.update(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUpdateMask("id".into()));
+
+        let request = abi::UpdateRequest::new(rsvp.id + 100, rsvp.clone(), &["note"]);
+        let err = manager.update(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+
+        let mut cancelled = rsvp.clone();
+        cancelled.status = abi::ReservationStatus::Cancelled as i32;
+        let request = abi::UpdateRequest::new(rsvp.id, cancelled, &["status"]);
+        let err = manager.update(request).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidStatus(abi::ReservationStatus::Cancelled as i32)
+        );
+
+        let mut late = rsvp.clone();
+        late.start = "2023-03-01T15:00:00-0700".parse().ok();
+        let request = abi::UpdateRequest::new(rsvp.id, late, &["start"]);
+        let err = manager.update(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTime);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn stale_version_should_abort_changes() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        assert_eq!(rsvp.version, 1);
+
+        // another admin changes the note first
+        let mut new = rsvp.clone();
+        new.note = "admin 1".into();
+        let mut request = abi::UpdateRequest::new(rsvp.id, new, &["note"]);
+        request.expected_version = Some(rsvp.version);
+        let rsvp1 = manager.update(request.clone()).await.unwrap();
+        assert_eq!(rsvp1.version, 2);
+
+        // the second write with the same version is rejected
+        let err = manager.update(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(1, 2));
+
+        let request = ConfirmRequest {
+            id: rsvp.id,
+            expected_version: Some(rsvp.version),
+        };
+        let err = manager.change_status(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(1, 2));
+
+        let request = CancelRequest {
+            id: rsvp.id,
+            reason: "".into(),
+            expected_version: Some(rsvp.version),
+        };
+        let err = manager.cancel(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(1, 2));
+        assert_eq!(manager.get(GetRequest::new(rsvp.id)).await.unwrap(), rsvp1);
+
+        let request = ConfirmRequest {
+            id: rsvp.id,
+            expected_version: Some(rsvp1.version),
+        };
+        let rsvp2 = manager.change_status(request).await.unwrap();
+        assert_eq!(rsvp2.version, 3);
+        assert_eq!(rsvp2.status, abi::ReservationStatus::Confirmed as i32);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reschedule_should_work() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let changes = count_changes(&migrated_pool).await;
+        let request = abi::RescheduleRequest::new(
+            rsvp.id,
+            "2023-03-01T15:00:00-0700".parse().unwrap(),
+            "2023-03-05T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let rsvp1 = manager.reschedule(request.clone()).await.unwrap();
+        assert_eq!(rsvp1.id, rsvp.id);
+        assert_eq!(rsvp1.status, rsvp.status);
+        assert_eq!(rsvp1.resource_id, "ixia-test-1");
+        assert_eq!(rsvp1.start, request.start);
+        assert_eq!(rsvp1.end, request.end);
+        assert_eq!(count_changes(&migrated_pool).await, changes + 1);
+
+        let request = abi::RescheduleRequest::new(
+            rsvp.id,
+            "2023-03-01T15:00:00-0700".parse().unwrap(),
+            "2023-03-05T12:00:00-0700".parse().unwrap(),
+            "ixia-test-2",
+        );
+        let rsvp2 = manager.reschedule(request).await.unwrap();
+        assert_eq!(rsvp2.resource_id, "ixia-test-2");
+        assert_eq!(rsvp2.start, rsvp1.start);
+        assert_eq!(count_changes(&migrated_pool).await, changes + 2);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reschedule_to_taken_window_should_reject() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let (rsvp2, _manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let request = abi::RescheduleRequest::new(
+            rsvp2.id,
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "ocean-view-room-713",
+        );
+        let err = manager.reschedule(request).await.unwrap_err();
+        assert!(
+            matches!(err, abi::Error::ConflictReservation { existing, .. } if existing == vec![rsvp1.clone()])
+        );
+
+        // the reservation stays where it was
+        assert_eq!(manager.get(GetRequest::new(rsvp2.id)).await.unwrap(), rsvp2);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reschedule_conflict_should_return_all_existing_reservations() {
+        let (rsvp1, manager) = make_tyr_reservation(migrated_pool.clone()).await;
+        let rsvp2 = manager
+            .reserve(abi::Reservation::new_pending(
+                "aliceid",
+                "ocean-view-room-713",
+                "2023-01-01T15:00:00-0700".parse().unwrap(),
+                "2023-01-03T12:00:00-0700".parse().unwrap(),
+                "",
+            ))
+            .await
+            .unwrap();
+        let rsvp3 = manager
+            .reserve(abi::Reservation::new_pending(
+                "bobid",
+                "ocean-view-room-713",
+                "2023-01-10T15:00:00-0700".parse().unwrap(),
+                "2023-01-12T12:00:00-0700".parse().unwrap(),
+                "",
+            ))
+            .await
+            .unwrap();
+
+        // stay on the same resource, the window covers both the others and itself
+        let request = abi::RescheduleRequest::new(
+            rsvp3.id,
+            "2022-12-20T15:00:00-0700".parse().unwrap(),
+            "2023-01-11T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let err = manager.reschedule(request).await.unwrap_err();
+        match err {
+            abi::Error::ConflictReservation { info, existing, .. } => {
+                assert!(
+                    matches!(*info, ReservationConflictInfo::Parsed(c) if c.new.rid == "ocean-view-room-713")
+                );
+                assert_eq!(existing, vec![rsvp1, rsvp2]);
+            }
+            e => panic!("expect conflict reservation error, got {:?}", e),
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reschedule_with_invalid_window_should_reject() {
+        let (rsvp, manager) = make_alice_reservation(migrated_pool.clone()).await;
+        let request = abi::RescheduleRequest::new(
+            rsvp.id,
+            "2023-03-05T12:00:00-0700".parse().unwrap(),
+            "2023-03-01T15:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let err = manager.reschedule(request).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTime);
+    }
+
     // private none test functions
+    fn updated_at(rsvp: &Reservation) -> DateTime<Utc> {
+        convert_to_utc_time(rsvp.updated_at.clone().unwrap())
+    }
+
+    async fn count_changes(pool: &PgPool) -> i64 {
+        sqlx::query_scalar("SELECT count(*) FROM rsvp.reservation_changes")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
     async fn make_tyr_reservation(pool: PgPool) -> (Reservation, ReservationManager) {
         make_reservation(
             pool,
@@ -1,10 +1,13 @@
 mod service;
+pub use service::{ACTOR_HEADER, NEXT_PAGE_TOKEN_HEADER};
 // #[cfg(feature = "test-utils")]
 // pub mod test_utils;
 #[cfg(test)]
 pub mod test_utils;
 
-use abi::{reservation_service_server::ReservationServiceServer, Config, Reservation};
+use abi::{
+    reservation_service_server::ReservationServiceServer, Config, ListenResponse, Reservation,
+};
 use futures::Stream;
 use reservation::ReservationManager;
 use std::pin::Pin;
@@ -20,7 +23,7 @@ pub struct TonicReceiverStream<T> {
 }
 
 type ReservationStream = Pin<Box<dyn Stream<Item = Result<Reservation, Status>> + Send>>;
-//type ListenStream = Pin<Box<dyn Stream<Item = Result<ListenResponse, Status>> + Send>>;
+type ListenStream = Pin<Box<dyn Stream<Item = Result<ListenResponse, Status>> + Send>>;
 // impl Deref for RsvpService {
 //     type Target = ReservationManager;
 //     fn deref(&self) -> &Self::Target {
@@ -1,9 +1,13 @@
 use std::{pin::Pin, task::Poll};
 
 use abi::{
-    reservation_service_server::ReservationService, CancelRequest, CancelResponse, Config,
-    ConfirmRequest, ConfirmResponse, FilterRequest, FilterResponse, GetRequest, GetResponse,
-    ListenRequest, QueryRequest, Reservation, ReserveRequest, ReserveResponse, UpdateResponse,
+    reservation_service_server::ReservationService, AvailabilityRequest, AvailabilityResponse,
+    BulkRequest, BulkResponse, CancelRequest, CancelResponse, CheckRequest, CheckResponse, Config,
+    ConfirmRequest, ConfirmResponse, FilterRequest, FilterResponse, FindSlotRequest,
+    FindSlotResponse, GetRequest, GetResponse, HistoryRequest, HistoryResponse, ListenRequest,
+    QueryRequest, RescheduleRequest, RescheduleResponse, ReservationStatus, ReserveManyRequest,
+    ReserveManyResponse, ReserveRequest, ReserveResponse, TransitionRequest, TransitionResponse,
+    UpdateRequest, UpdateResponse,
 };
 use futures::Stream;
 use reservation::{ReservationManager, Rsvp};
@@ -12,7 +16,14 @@ use tokio::sync::mpsc;
 
 use tonic::{async_trait, Request, Response, Status};
 
-use crate::{ReservationStream, RsvpService, TonicReceiverStream};
+use crate::{ListenStream, ReservationStream, RsvpService, TonicReceiverStream};
+
+/// request metadata naming who makes the change, recorded in the reservation history.
+/// The service does not authenticate it, so the recorded actor is informational only
+pub const ACTOR_HEADER: &str = "x-actor";
+
+/// response metadata of a paged query, holding the token of the next page
+pub const NEXT_PAGE_TOKEN_HEADER: &str = "x-next-page-token";
 
 impl RsvpService {
     pub async fn from_config(config: &Config) -> Result<Self, anyhow::Error> {
@@ -20,6 +31,18 @@ impl RsvpService {
             manager: ReservationManager::from_config(&config.db).await?,
         })
     }
+
+    /// the manager to make changes for the request, with the actor taken from the request metadata
+    fn manager_for<T>(&self, request: &Request<T>) -> ReservationManager {
+        match request
+            .metadata()
+            .get(ACTOR_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(actor) if !actor.is_empty() => self.manager.with_actor(actor),
+            _ => self.manager.clone(),
+        }
+    }
 }
 
 #[async_trait]
@@ -29,44 +52,84 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ReserveRequest>,
     ) -> Result<Response<ReserveResponse>, Status> {
+        let manager = self.manager_for(&request);
         let request = request.into_inner();
         if request.reservation.is_none() {
             return Err(Status::invalid_argument("missing reservation"));
         }
-        let reservation = self.manager.reserve(request.reservation.unwrap()).await?;
+        let reservation = manager.reserve_or_suggest(request).await?;
         Ok(Response::new(ReserveResponse {
             reservation: Some(reservation),
         }))
     }
-    /// confirm a pending reservation, if the reservation is not pending, do nothing
+    /// make several reservations in one transaction, all conflicts are reported if any
+    async fn reserve_many(
+        &self,
+        request: Request<ReserveManyRequest>,
+    ) -> Result<Response<ReserveManyResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let reservations = manager.reserve_many(request.reservations).await?;
+        Ok(Response::new(ReserveManyResponse { reservations }))
+    }
+    /// confirm a pending reservation. A confirmed one is returned as is, any other status is an invalid transition
     async fn confirm(
         &self,
         request: Request<ConfirmRequest>,
     ) -> Result<Response<ConfirmResponse>, Status> {
+        let manager = self.manager_for(&request);
         let request = request.into_inner();
-        let reservation = self.manager.change_status(request.id).await?;
+        let reservation = manager.change_status(request).await?;
         Ok(Response::new(ConfirmResponse {
             reservation: Some(reservation),
         }))
     }
-    /// update reservation note
+    /// move a reservation to another status
+    async fn transition(
+        &self,
+        request: Request<TransitionRequest>,
+    ) -> Result<Response<TransitionResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let status = ReservationStatus::from_i32(request.status)
+            .ok_or(abi::Error::InvalidStatus(request.status))?;
+        let reservation = manager.transition(request.id, status).await?;
+        Ok(Response::new(TransitionResponse {
+            reservation: Some(reservation),
+        }))
+    }
+    /// update the masked fields of a reservation
     async fn update(
         &self,
-        request: Request<Reservation>,
+        request: Request<UpdateRequest>,
     ) -> Result<Response<UpdateResponse>, Status> {
+        let manager = self.manager_for(&request);
         let request = request.into_inner();
-        let reservation = self.manager.update_note(request.id, request.note).await?;
+        let reservation = manager.update(request).await?;
         Ok(Response::new(UpdateResponse {
             reservation: Some(reservation),
         }))
     }
+    /// move a reservation to another time window or resource
+    async fn reschedule(
+        &self,
+        request: Request<RescheduleRequest>,
+    ) -> Result<Response<RescheduleResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let reservation = manager.reschedule(request).await?;
+        Ok(Response::new(RescheduleResponse {
+            reservation: Some(reservation),
+        }))
+    }
     /// cancel a reservation
     async fn cancel(
         &self,
         request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
+        let manager = self.manager_for(&request);
         let request = request.into_inner();
-        let reservation = self.manager.delete(request.id).await?;
+        let reservation = manager.cancel(request).await?;
         Ok(Response::new(CancelResponse {
             reservation: Some(reservation),
         }))
@@ -74,7 +137,7 @@ impl ReservationService for RsvpService {
     /// get a reservation by id
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
         let request = request.into_inner();
-        let reservation = self.manager.get(request.id).await?;
+        let reservation = self.manager.get(request).await?;
         Ok(Response::new(GetResponse {
             reservation: Some(reservation),
         }))
@@ -87,12 +150,29 @@ impl ReservationService for RsvpService {
         request: Request<QueryRequest>,
     ) -> Result<Response<Self::queryStream>, Status> {
         let request = request.into_inner();
-        if request.query.is_none() {
-            return Err(Status::invalid_argument("missing query params"));
+        let query = match request.query {
+            Some(query) => query,
+            None => return Err(Status::invalid_argument("missing query params")),
+        };
+        if query.page_size == 0 {
+            let rsvps = self.manager.query(query).await;
+            let stream = TonicReceiverStream::new(rsvps);
+            return Ok(Response::new(Box::pin(stream)));
         }
-        let rsvps = self.manager.query(request.query.unwrap()).await;
-        let stream = TonicReceiverStream::new(rsvps);
-        Ok(Response::new(Box::pin(stream)))
+
+        // a page is small enough to be fetched before responding, so its token could be sent in metadata
+        let (token, rsvps) = self.manager.query_page(query).await?;
+        let mut response: Response<Self::queryStream> =
+            Response::new(Box::pin(futures::stream::iter(rsvps.into_iter().map(Ok))));
+        if let Some(token) = token {
+            let token = token
+                .parse()
+                .map_err(|_| Status::internal("invalid page token"))?;
+            response
+                .metadata_mut()
+                .insert(NEXT_PAGE_TOKEN_HEADER, token);
+        }
+        Ok(response)
     }
     /// filter reservations, order by reservation id
     async fn filter(
@@ -110,13 +190,67 @@ impl ReservationService for RsvpService {
         }))
     }
     ///Server streaming response type for the listen method.
-    type listenStream = ReservationStream;
+    type listenStream = ListenStream;
     /// another system could monitor newly added/confirmed/cancelled reservations
     async fn listen(
         &self,
-        _request: Request<ListenRequest>,
+        request: Request<ListenRequest>,
     ) -> Result<Response<Self::listenStream>, Status> {
-        todo!()
+        let request = request.into_inner();
+        let changes = self.manager.listen(request).await;
+        let stream = TonicReceiverStream::new(changes);
+        Ok(Response::new(Box::pin(stream)))
+    }
+    /// get free time windows of a resource
+    async fn availability(
+        &self,
+        request: Request<AvailabilityRequest>,
+    ) -> Result<Response<AvailabilityResponse>, Status> {
+        let request = request.into_inner();
+        let slots = self.manager.availability(request).await?;
+        Ok(Response::new(AvailabilityResponse { slots }))
+    }
+    /// check if a reservation could be made, without making it
+    async fn check(
+        &self,
+        request: Request<CheckRequest>,
+    ) -> Result<Response<CheckResponse>, Status> {
+        let request = request.into_inner();
+        if request.reservation.is_none() {
+            return Err(Status::invalid_argument("missing reservation"));
+        }
+        let conflicts = self.manager.check(request.reservation.unwrap()).await?;
+        Ok(Response::new(CheckResponse {
+            conflicts: conflicts.into_iter().map(Into::into).collect(),
+        }))
+    }
+    /// confirm, cancel or note all the reservations selected by a filter or query
+    async fn bulk(&self, request: Request<BulkRequest>) -> Result<Response<BulkResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let reservations = manager.bulk(request.into_inner()).await?;
+        Ok(Response::new(BulkResponse { reservations }))
+    }
+    /// get the audit history of a reservation
+    async fn history(
+        &self,
+        request: Request<HistoryRequest>,
+    ) -> Result<Response<HistoryResponse>, Status> {
+        let request = request.into_inner();
+        let changes = self.manager.history(request.reservation_id).await?;
+        Ok(Response::new(HistoryResponse { changes }))
+    }
+    /// find the earliest free slot from a pool of resources, and optionally reserve it
+    async fn find_slot(
+        &self,
+        request: Request<FindSlotRequest>,
+    ) -> Result<Response<FindSlotResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let (slot, reservation) = manager.find_slot(request).await?;
+        Ok(Response::new(FindSlotResponse {
+            slot: Some(slot),
+            reservation,
+        }))
     }
 }
 
@@ -160,9 +294,7 @@ mod tests {
             "2022-12-30T12:00:00-0700".parse().unwrap(),
             "test device reservation",
         );
-        let request = tonic::Request::new(ReserveRequest {
-            reservation: Some(reservation.clone()),
-        });
+        let request = tonic::Request::new(ReserveRequest::new(reservation.clone()));
         let response = service.reserve(request).await.unwrap();
         let reservation1 = response.into_inner().reservation;
         assert!(reservation1.is_some());
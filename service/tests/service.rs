@@ -3,15 +3,16 @@ mod test_utils;
 use std::time::Duration;
 
 use abi::{
-    reservation_service_client::ReservationServiceClient, Config, ConfirmRequest, FilterRequest,
-    FilterResponse, QueryRequest, Reservation, ReservationFilterBuilder, ReservationQueryBuilder,
-    ReservationStatus, ReserveRequest,
+    reservation_service_client::ReservationServiceClient, BulkAction, BulkRequest, Config,
+    ConfirmRequest, ErrorDetails, ErrorReason, FilterRequest, FilterResponse, HistoryRequest,
+    ListenRequest, QueryRequest, Reservation, ReservationFilterBuilder, ReservationQueryBuilder,
+    ReservationStatus, ReservationUpdateType, ReserveRequest,
 };
 use futures::StreamExt;
-use reservation_service::start_server;
+use reservation_service::{start_server, ACTOR_HEADER, NEXT_PAGE_TOKEN_HEADER};
 use test_utils::TestConfig;
 use tokio::time;
-use tonic::transport::Channel;
+use tonic::{transport::Channel, Request};
 
 #[tokio::test]
 async fn grpc_server_should_work() {
@@ -34,6 +35,9 @@ async fn grpc_server_should_work() {
         .reservation
         .unwrap();
     rsvp.id = ret.id;
+    rsvp.created_at = ret.created_at.clone();
+    rsvp.updated_at = ret.updated_at.clone();
+    rsvp.version = ret.version;
     assert_eq!(ret, rsvp);
     // then we try to make a conflict reservation
     let rsvp2 = Reservation::new_pending(
@@ -43,8 +47,22 @@ async fn grpc_server_should_work() {
         "2022-12-30T12:00:00-0700".parse().unwrap(),
         "test device reservation",
     );
-    let ret = client.reserve(ReserveRequest::new(rsvp2.clone())).await;
-    assert!(ret.is_err());
+    let ret = client
+        .reserve(ReserveRequest::with_suggestions(rsvp2.clone(), 2))
+        .await;
+    let status = ret.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let details = ErrorDetails::from_status(&status).unwrap();
+    assert_eq!(details.reason, ErrorReason::ConflictReservation);
+    assert_eq!(details.conflicts.len(), 1);
+    let conflict = details.conflicts[0].clone();
+    assert_eq!(conflict.new.unwrap().resource_id, "ixia-3230");
+    assert_eq!(conflict.old.unwrap().start, rsvp.start);
+    assert_eq!(conflict.existing, Some(rsvp.clone()));
+    // the slots right before and right after the existing one, equally near, earlier first
+    assert_eq!(details.suggestions.len(), 2);
+    assert_eq!(details.suggestions[0].end, rsvp.start);
+    assert_eq!(details.suggestions[1].start, rsvp.end);
 
     // then we try to confirm the first reservation
     let ret = client
@@ -79,6 +97,98 @@ async fn grpc_query_should_work() {
     }
 }
 
+#[tokio::test]
+async fn grpc_query_should_page_with_token() {
+    let tconfig = TestConfig::with_server_port(50005);
+    let mut client = get_test_client(&tconfig).await;
+    make_reservations(&mut client, 25).await;
+
+    let mut ids = Vec::new();
+    let mut pages = Vec::new();
+    let mut token = String::new();
+    loop {
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .page_size(10)
+            .page_token(token)
+            .build()
+            .unwrap();
+        let ret = client.query(QueryRequest::new(query)).await.unwrap();
+        let next = ret
+            .metadata()
+            .get(NEXT_PAGE_TOKEN_HEADER)
+            .map(|v| v.to_str().unwrap().to_string());
+        let page: Vec<_> = ret.into_inner().map(|r| r.unwrap().id).collect().await;
+        pages.push(page.len());
+        ids.extend(page);
+        match next {
+            Some(next) => token = next,
+            None => break,
+        }
+    }
+    assert_eq!(pages, vec![10, 10, 5]);
+    let mut sorted = ids.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), 25);
+    // all reservations start at the same time, so they are ordered by id
+    assert_eq!(ids, sorted);
+}
+
+#[tokio::test]
+async fn grpc_bulk_should_cancel_by_filter() {
+    let tconfig = TestConfig::with_server_port(50006);
+    let mut client = get_test_client(&tconfig).await;
+    make_reservations(&mut client, 5).await;
+
+    let filter = ReservationFilterBuilder::default()
+        .user_id("alice")
+        .build()
+        .unwrap();
+    let mut bulk = BulkRequest::new(filter.clone(), BulkAction::Cancel);
+    bulk.note = "lab closed".into();
+    bulk.dry_run = true;
+    let ret = client.bulk(bulk.clone()).await.unwrap().into_inner();
+    assert_eq!(ret.reservations.len(), 5);
+
+    // nothing is cancelled by the dry run
+    let pending = client
+        .filter(FilterRequest::new(filter.clone()))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservations;
+    assert_eq!(pending.len(), 5);
+
+    bulk.dry_run = false;
+    let mut request = Request::new(bulk);
+    request
+        .metadata_mut()
+        .insert(ACTOR_HEADER, "support-bob".parse().unwrap());
+    let ret = client.bulk(request).await.unwrap().into_inner();
+    assert_eq!(ret.reservations.len(), 5);
+    assert!(ret.reservations.iter().all(
+        |r| r.status == ReservationStatus::Cancelled as i32 && r.cancel_reason == "lab closed"
+    ));
+    let pending = client
+        .filter(FilterRequest::new(filter))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservations;
+    assert!(pending.is_empty());
+
+    let changes = client
+        .history(HistoryRequest {
+            reservation_id: ret.reservations[0].id,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .changes;
+    assert_eq!(changes.last().unwrap().actor, "support-bob");
+}
+
 #[tokio::test]
 async fn grpc_filter_should_work() {
     let tconfig = TestConfig::with_server_port(50002);
@@ -125,13 +235,96 @@ async fn grpc_filter_should_work() {
     // assert_eq!(reservations.len(), filter.page_size as usize);
 }
 
+#[tokio::test]
+async fn grpc_listen_should_work() {
+    let tconfig = TestConfig::with_server_port(50003);
+    let mut client = get_test_client(&tconfig).await;
+    let mut stream = client
+        .clone()
+        .listen(ListenRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+
+    make_reservations(&mut client, 3).await;
+
+    let mut changes = vec![];
+    for _ in 0..3 {
+        let change = stream.next().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        assert_eq!(change.reservation.as_ref().unwrap().user_id, "alice");
+        changes.push(change);
+    }
+
+    // reconnect with the first change id, we should get the rest
+    let mut stream = client
+        .listen(ListenRequest::new(changes[0].change_id))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(stream.next().await.unwrap().unwrap(), changes[1]);
+    assert_eq!(stream.next().await.unwrap().unwrap(), changes[2]);
+}
+
+#[tokio::test]
+async fn grpc_history_should_record_actor() {
+    let tconfig = TestConfig::with_server_port(50004);
+    let mut client = get_test_client(&tconfig).await;
+    let rsvp = Reservation::new_pending(
+        "tyr",
+        "ixia-3230",
+        "2022-12-26T15:00:00-0700".parse().unwrap(),
+        "2022-12-30T12:00:00-0700".parse().unwrap(),
+        "test device reservation",
+    );
+    let mut request = Request::new(ReserveRequest::new(rsvp));
+    request
+        .metadata_mut()
+        .insert(ACTOR_HEADER, "support-bob".parse().unwrap());
+    let rsvp = client
+        .reserve(request)
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    client.confirm(ConfirmRequest::new(rsvp.id)).await.unwrap();
+
+    let changes = client
+        .history(HistoryRequest {
+            reservation_id: rsvp.id,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .changes;
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].op, ReservationUpdateType::Create as i32);
+    assert_eq!(changes[0].actor, "support-bob");
+    assert_eq!(changes[0].new, Some(rsvp.clone()));
+    assert_eq!(changes[1].op, ReservationUpdateType::Update as i32);
+    assert_eq!(changes[1].actor, "");
+    assert_eq!(changes[1].old, Some(rsvp));
+    assert_eq!(
+        changes[1].new.as_ref().unwrap().status,
+        ReservationStatus::Confirmed as i32
+    );
+}
+
 async fn get_test_client(tconfig: &TestConfig) -> ReservationServiceClient<Channel> {
     let config = &tconfig.config;
     setup_server(config).await;
 
-    ReservationServiceClient::connect(config.server.url(false))
-        .await
-        .unwrap()
+    // server may need more time to connect to db, so retry until it is ready
+    let fut = async move {
+        loop {
+            match ReservationServiceClient::connect(config.server.url(false)).await {
+                Ok(client) => break client,
+                Err(_) => time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    };
+    time::timeout(Duration::from_secs(5), fut).await.unwrap()
 }
 
 async fn setup_server(config: &Config) {
@@ -165,6 +358,9 @@ async fn make_reservations(client: &mut ReservationServiceClient<Channel>, count
             .reservation
             .unwrap();
         rsvp.id = ret.id;
+        rsvp.created_at = ret.created_at.clone();
+        rsvp.updated_at = ret.updated_at.clone();
+        rsvp.version = ret.version;
         assert_eq!(ret, rsvp);
     }
 }